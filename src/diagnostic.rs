@@ -0,0 +1,190 @@
+use std::{fmt::Write, ops::Range};
+
+use crate::{ErrorKind, IRCv3TagsError};
+
+/// Lines longer than this many characters are cut around the error when rendered.
+const MAX_RENDERED_CHARS: usize = 100;
+
+/// Characters shown on each side of the error in a cut line.
+const CONTEXT_CHARS: usize = 40;
+
+/// Where an error is in the parsed line.
+///
+/// Returned by the `location` method of the error types, e.g.
+/// [`IRCv3TagsError::location`](crate::IRCv3TagsError::location).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Byte offset of the error.
+    pub offset: usize,
+    /// Character offset of the error.
+    pub char_offset: usize,
+    /// Byte range of the offending tag, from its start to its end, or of the whole tags
+    /// section for [`ErrorKind::TooLong`] and [`ErrorKind::TooManyTags`].
+    /// Other errors span from the error to the end of the tag.
+    pub span: Range<usize>,
+}
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// An invalid tag was skipped.
+    Error,
+    /// The tags were accepted, but do not follow the specification,
+    /// e.g. an empty segment allowed by
+    /// [`IRCv3TagsParser::allow_empty_tags`](crate::tags::IRCv3TagsParser::allow_empty_tags).
+    Warning,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// A problem with a single tag segment, reported by
+/// [`IRCv3TagsParser::recover`](crate::tags::IRCv3TagsParser::recover) and
+/// [`IRCv3TagsParser::debug_parse_with_warnings`](crate::tags::IRCv3TagsParser::debug_parse_with_warnings).
+#[derive(Debug, PartialEq)]
+pub struct Diagnostic<'a> {
+    /// Whether the segment was skipped as invalid or only tolerated.
+    pub severity: Severity,
+    /// Byte range of the `key=value` segment in the parsed line.
+    pub span: Range<usize>,
+    /// What is wrong with the segment.
+    pub error: IRCv3TagsError<&'a str>,
+}
+
+impl Diagnostic<'_> {
+    /// Renders the line with a `^~~~` caret under the problem, see [`IRCv3TagsError::render`].
+    pub fn render(&self, line: &str) -> String {
+        render_with(
+            line,
+            self.error.input,
+            self.error.error,
+            self.severity,
+            self.error.reason,
+        )
+    }
+}
+
+/// Finds `input` in `line`, either as a subslice of it or as a suffix of it,
+/// and the span `kind` covers there.
+pub(crate) fn locate(line: &str, input: &str, kind: ErrorKind) -> Option<Location> {
+    let start = line.as_ptr() as usize;
+    let ptr = input.as_ptr() as usize;
+
+    let offset = if ptr >= start && ptr + input.len() <= start + line.len() {
+        ptr - start
+    } else if line.ends_with(input) {
+        line.len() - input.len()
+    } else {
+        return None;
+    };
+
+    let span = match kind {
+        ErrorKind::TooLong(_) | ErrorKind::TooManyTags if line.starts_with('@') => {
+            0..line.find([' ', '\r', '\n', '\0']).unwrap_or(line.len())
+        }
+        ErrorKind::EmptyKey
+        | ErrorKind::InvalidKeyStart
+        | ErrorKind::InvalidKeyChar
+        | ErrorKind::InvalidVendor
+        | ErrorKind::ForbiddenValueChar
+        | ErrorKind::DuplicateKey
+        | ErrorKind::InvalidEscape => tag_start(line, offset)..segment_end(line, offset),
+        _ => offset..segment_end(line, offset),
+    };
+
+    Some(Location {
+        offset,
+        char_offset: line[..offset].chars().count(),
+        span,
+    })
+}
+
+/// Returns the offset of the tag containing `offset`, just after the ';' or '@' before it.
+fn tag_start(line: &str, offset: usize) -> usize {
+    line[..offset]
+        .rfind([';', ' '])
+        .map_or(usize::from(line.starts_with('@')), |i| i + 1)
+        .min(offset)
+}
+
+/// Returns the offset of the ';', space or line terminator ending the tag at `offset`.
+fn segment_end(line: &str, offset: usize) -> usize {
+    line[offset..]
+        .find([';', ' ', '\r', '\n', '\0'])
+        .map_or(line.len(), |end| offset + end)
+}
+
+/// Renders `line` with a `^~~~` caret under the error, like rustc.
+///
+/// ```text
+/// error: tag key must start with an allowed character
+///  --> byte 5, char 5
+///   |
+///   | @a=1;-b PRIVMSG #channel
+///   |      ^~
+/// ```
+pub(crate) fn render(line: &str, input: &str, kind: ErrorKind, reason: &str) -> String {
+    render_with(line, input, kind, Severity::Error, reason)
+}
+
+fn render_with(
+    line: &str,
+    input: &str,
+    kind: ErrorKind,
+    severity: Severity,
+    reason: &str,
+) -> String {
+    let mut out = format!("{}: {}\n", severity, reason);
+
+    let Some(location) = locate(line, input, kind) else {
+        return out;
+    };
+
+    let line = line.trim_end_matches(['\r', '\n']);
+    let char_at = |offset: usize| line[..offset.min(line.len())].chars().count();
+    let span_start = char_at(location.span.start).min(location.char_offset);
+    let span_end = char_at(location.span.end).max(location.char_offset + 1);
+
+    let total_chars = line.chars().count();
+    let (skip, prefix) = if total_chars > MAX_RENDERED_CHARS && location.char_offset > CONTEXT_CHARS
+    {
+        (location.char_offset - CONTEXT_CHARS, "...")
+    } else {
+        (0, "")
+    };
+    let take = if total_chars > MAX_RENDERED_CHARS {
+        let tail = span_end - location.char_offset;
+        location.char_offset - skip + tail.min(CONTEXT_CHARS) + CONTEXT_CHARS
+    } else {
+        total_chars
+    };
+    let suffix = if skip + take < total_chars { "..." } else { "" };
+    let shown = line.chars().skip(skip).take(take).collect::<String>();
+
+    // '^' at the error, '~' under the rest of the span that is shown
+    let underline = (span_start.max(skip)..span_end.min(skip + take).max(location.char_offset + 1))
+        .map(|i| if i == location.char_offset { '^' } else { '~' })
+        .collect::<String>();
+
+    let _ = writeln!(
+        out,
+        " --> byte {}, char {}",
+        location.offset, location.char_offset
+    );
+    out.push_str("  |\n");
+    let _ = writeln!(out, "  | {}{}{}", prefix, shown, suffix);
+    let _ = writeln!(
+        out,
+        "  | {}{}",
+        " ".repeat(prefix.len() + span_start.max(skip) - skip),
+        underline
+    );
+
+    out
+}
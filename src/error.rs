@@ -1,45 +1,261 @@
-#[derive(Debug, PartialEq)]
+use std::fmt;
+
+use crate::{host::HostError, tags::TagLimit};
+
+/// What went wrong while parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ErrorKind {
+    /// Invalid host
     Host,
-    TagErrorStartWithLetter,
+    /// A required part is empty, e.g. a host label or a command
     Empty,
+    /// Failure reported by a nom combinator
     NomError,
+    /// Invalid command
+    Command,
+    /// The tags do not start with an '@'
+    MissingAt,
+    /// A tag has no key, e.g. `@=value` or `@a;;b`
+    EmptyKey,
+    /// The tag key starts with a character the key validator does not allow
+    InvalidKeyStart,
+    /// The tag key contains a character the key validator does not allow
+    InvalidKeyChar,
+    /// The vendor prefix of a tag key is not a valid host
+    InvalidVendor,
+    /// The tags are not followed by a space
+    MissingSpace,
+    /// The tags are followed by more than one space, see
+    /// [`TagsSeparator::SingleSpace`](crate::tags::TagsSeparator::SingleSpace)
+    ExtraSpace,
+    /// A tag value contains NUL, CR or LF
+    ForbiddenValueChar,
+    /// More tags than [`IRCv3TagsParser::max_tags`](crate::tags::IRCv3TagsParser::max_tags)
+    TooManyTags,
+    /// The tag data exceeds a size limit
+    TooLong(TagLimit),
+    /// A tag key is repeated, see [`DuplicateKeyPolicy::Reject`](crate::tags::DuplicateKeyPolicy::Reject)
+    DuplicateKey,
+    /// A tag value contains an invalid escape, see
+    /// [`IRCv3TagsParser::reject_invalid_escapes`](crate::tags::IRCv3TagsParser::reject_invalid_escapes)
+    InvalidEscape,
 }
 
-#[derive(Debug, PartialEq)]
+impl ErrorKind {
+    /// The closest nom error kind, used by the parsers returning `nom::error::Error`.
+    pub(crate) fn nom_code(&self) -> nom::error::ErrorKind {
+        match self {
+            Self::TooLong(_) | Self::TooManyTags => nom::error::ErrorKind::TooLarge,
+            Self::DuplicateKey | Self::InvalidEscape => nom::error::ErrorKind::Verify,
+            Self::MissingSpace | Self::ExtraSpace => nom::error::ErrorKind::Space,
+            Self::NomError => nom::error::ErrorKind::Fail,
+            _ => nom::error::ErrorKind::Char,
+        }
+    }
+}
+
+/// Error returned by the tags parser.
+///
+/// When a vendor prefix is not a valid host, the [`HostError`] is kept as the
+/// [`source`](std::error::Error::source) of this error.
+///
+/// # Examples
+///
+/// ```
+/// use std::error::Error;
+///
+/// use ircv3_tags::IRCv3TagsError;
+///
+/// fn vendors(input: &str) -> Result<Vec<String>, Box<dyn Error>> {
+///     let (_, tags) = ircv3_tags::debug_parse(input)
+///         .map_err(|err| IRCv3TagsError::from(err.map(IRCv3TagsError::into_owned)))?;
+///
+///     Ok(tags.keys().filter_map(|key| key.vendor().map(String::from)).collect())
+/// }
+///
+/// let err = vendors("@example-.com/foo=bar PRIVMSG").unwrap_err();
+/// assert_eq!(err.to_string(), "tag key has an invalid vendor at \"example-.com/foo=bar\"");
+/// assert_eq!(
+///     err.source().unwrap().to_string(),
+///     "label contains an invalid character at \"example-.com/foo=bar\""
+/// );
+/// ```
+///
+/// [`location`](Self::location) and [`render`](Self::render) point at the error in the
+/// parsed line:
+///
+/// ```
+/// use ircv3_tags::Location;
+///
+/// let line = "@id=1;+typing;-bad=3 PRIVMSG #channel";
+/// let nom::Err::Error(err) = ircv3_tags::debug_parse(line).unwrap_err() else {
+///     panic!("expected an error");
+/// };
+///
+/// assert_eq!(
+///     err.location(line),
+///     Some(Location { offset: 14, char_offset: 14, span: 14..20 })
+/// );
+/// assert_eq!(
+///     err.render(line),
+///     "error: tag key must start with an allowed character
+///  --> byte 14, char 14
+///   |
+///   | @id=1;+typing;-bad=3 PRIVMSG #channel
+///   |               ^~~~~~
+/// "
+/// );
+/// ```
+#[derive(Debug)]
 pub struct IRCv3TagsError<I> {
     pub input: I,
-    pub code: nom::error::ErrorKind,
     pub error: ErrorKind,
     pub reason: &'static str,
+    pub(crate) host: Option<Box<HostError<I>>>,
 }
+
 impl<I> IRCv3TagsError<I> {
-    pub fn new(
-        input: I,
-        code: nom::error::ErrorKind,
-        error: ErrorKind,
-        reason: &'static str,
-    ) -> Self {
+    /// Returns the host error of an invalid vendor prefix.
+    pub fn host_error(&self) -> Option<&HostError<I>> {
+        self.host.as_deref()
+    }
+}
+
+parse_error!(IRCv3TagsError, "IRCv3 message tags", host);
+
+/// Wraps the host error of an invalid vendor prefix.
+impl<I: Clone> From<HostError<I>> for IRCv3TagsError<I> {
+    fn from(e: HostError<I>) -> Self {
         Self {
-            input,
-            code,
-            error,
-            reason,
+            input: e.input.clone(),
+            error: ErrorKind::InvalidVendor,
+            reason: "tag key has an invalid vendor",
+            host: Some(Box::new(e)),
         }
     }
 }
 
-impl<I> nom::error::ParseError<I> for IRCv3TagsError<I> {
-    fn from_error_kind(input: I, kind: nom::error::ErrorKind) -> Self {
-        IRCv3TagsError {
-            input,
-            code: kind,
-            error: ErrorKind::NomError,
-            reason: "failed to parse IRCv3 message tags",
-        }
+/// Writes `reason` followed by the start of the remaining input.
+pub(crate) fn fmt_error(f: &mut fmt::Formatter<'_>, reason: &str, input: &str) -> fmt::Result {
+    f.write_str(reason)?;
+
+    if input.is_empty() {
+        return f.write_str(" at end of input");
     }
 
-    fn append(_: I, _: nom::error::ErrorKind, other: Self) -> Self {
-        other
+    let end = input
+        .find([' ', '\r', '\n'])
+        .filter(|&end| end > 0)
+        .unwrap_or(input.len());
+    let near = &input[..end];
+    match near.char_indices().nth(32) {
+        Some((end, _)) => write!(f, " at {:?}...", &near[..end]),
+        None => write!(f, " at {:?}", near),
     }
 }
+
+/// Implements what the parse errors share: `new`, `location`, `render`, `into_owned`,
+/// `PartialEq`, `Display`, `Error` and the nom conversions.
+///
+/// `$what` names the parsed input in the nom error reasons. Errors wrapping a
+/// [`HostError`] name their `Option<Box<HostError<I>>>` field, which is left out of
+/// comparisons and returned by [`source`](std::error::Error::source).
+macro_rules! parse_error {
+    ($name:ident, $what:literal $(, $host:ident)?) => {
+        impl<I> $name<I> {
+            /// Creates an error at `input`, without a source error.
+            pub fn new(input: I, error: $crate::ErrorKind, reason: &'static str) -> Self {
+                Self {
+                    input,
+                    error,
+                    reason,
+                    $($host: None,)?
+                }
+            }
+        }
+
+        impl<I: AsRef<str>> $name<I> {
+            /// Returns where the error is in `line`, the input given to the parser.
+            ///
+            /// Returns `None` if the error input is not part of `line`.
+            pub fn location(&self, line: &str) -> Option<$crate::Location> {
+                $crate::diagnostic::locate(line, self.input.as_ref(), self.error)
+            }
+
+            /// Renders the line with a `^~~~` caret under the error, like rustc.
+            pub fn render(&self, line: &str) -> String {
+                $crate::diagnostic::render(line, self.input.as_ref(), self.error, self.reason)
+            }
+        }
+
+        impl $name<&str> {
+            /// Copies the input so the error can outlive it.
+            pub fn into_owned(self) -> $name<String> {
+                $name {
+                    input: self.input.to_string(),
+                    error: self.error,
+                    reason: self.reason,
+                    $($host: self.$host.map(|e| Box::new(e.into_owned())),)?
+                }
+            }
+        }
+
+        /// Errors are equal when they have the same input, kind and reason.
+        impl<I: PartialEq> PartialEq for $name<I> {
+            fn eq(&self, other: &Self) -> bool {
+                self.input == other.input && self.error == other.error && self.reason == other.reason
+            }
+        }
+
+        impl<I: AsRef<str>> std::fmt::Display for $name<I> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                $crate::error::fmt_error(f, self.reason, self.input.as_ref())
+            }
+        }
+
+        $crate::error::parse_error!(@error $name $(, $host)?);
+
+        impl<I: Default> From<nom::Err<$name<I>>> for $name<I> {
+            fn from(err: nom::Err<$name<I>>) -> Self {
+                match err {
+                    nom::Err::Error(e) | nom::Err::Failure(e) => e,
+                    nom::Err::Incomplete(_) => Self::new(
+                        I::default(),
+                        $crate::ErrorKind::NomError,
+                        concat!("incomplete ", $what),
+                    ),
+                }
+            }
+        }
+
+        impl<I> nom::error::ParseError<I> for $name<I> {
+            fn from_error_kind(input: I, _: nom::error::ErrorKind) -> Self {
+                Self::new(
+                    input,
+                    $crate::ErrorKind::NomError,
+                    concat!("failed to parse ", $what),
+                )
+            }
+
+            fn append(_: I, _: nom::error::ErrorKind, other: Self) -> Self {
+                other
+            }
+        }
+    };
+
+    (@error $name:ident) => {
+        impl<I: AsRef<str> + std::fmt::Debug> std::error::Error for $name<I> {}
+    };
+
+    (@error $name:ident, $host:ident) => {
+        impl<I: AsRef<str> + std::fmt::Debug + 'static> std::error::Error for $name<I> {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                self.$host
+                    .as_deref()
+                    .map(|e| e as &(dyn std::error::Error + 'static))
+            }
+        }
+    };
+}
+
+pub(crate) use parse_error;
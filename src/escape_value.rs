@@ -0,0 +1,98 @@
+use std::{borrow::Cow, fmt, io};
+
+/// Escapes a value for use in an IRCv3 tag according to the specification.
+///
+/// This is the inverse of [`unescaped_to_escaped`](crate::unescaped_to_escaped).
+/// The following characters are escaped:
+/// - `;` → `\:` (semicolon → backslash + colon)
+/// - ` ` → `\s` (space → backslash + s)
+/// - `\` → `\\` (backslash → backslash + backslash)
+/// - CR → `\r` (carriage return → backslash + r)
+/// - LF → `\n` (line feed → backslash + n)
+///
+/// Returns `Cow::Borrowed` when the value contains none of these characters.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+/// use ircv3_tags::escape_value;
+///
+/// assert_eq!(escape_value("hello world"), "hello\\sworld");
+/// assert_eq!(escape_value("semi;colon"), "semi\\:colon");
+/// assert_eq!(escape_value("back\\slash"), "back\\\\slash");
+/// assert!(matches!(escape_value("plain"), Cow::Borrowed("plain")));
+/// ```
+pub fn escape_value(value: &str) -> Cow<'_, str> {
+    match value.find(needs_escape) {
+        None => Cow::Borrowed(value),
+        Some(position) => {
+            let mut result = String::with_capacity(value.len() + 8);
+            result.push_str(&value[..position]);
+            // Writing into a String never fails
+            let _ = escape_value_fmt(&value[position..], &mut result);
+            Cow::Owned(result)
+        }
+    }
+}
+
+/// Escapes a value into a [`fmt::Write`].
+///
+/// # Examples
+///
+/// ```
+/// let mut out = String::from("@msg=");
+/// ircv3_tags::escape_value_fmt("a b", &mut out).unwrap();
+/// assert_eq!(out, "@msg=a\\sb");
+/// ```
+pub fn escape_value_fmt<W: fmt::Write>(value: &str, writer: &mut W) -> fmt::Result {
+    let mut start = 0;
+
+    for (i, c) in value.char_indices() {
+        if let Some(escaped) = escape(c) {
+            writer.write_str(&value[start..i])?;
+            writer.write_str(escaped)?;
+            start = i + c.len_utf8();
+        }
+    }
+
+    writer.write_str(&value[start..])
+}
+
+/// Escapes a value into an [`io::Write`].
+///
+/// # Examples
+///
+/// ```
+/// let mut out = Vec::new();
+/// ircv3_tags::escape_value_io("a;b", &mut out).unwrap();
+/// assert_eq!(out, b"a\\:b");
+/// ```
+pub fn escape_value_io<W: io::Write>(value: &str, writer: &mut W) -> io::Result<()> {
+    let mut start = 0;
+
+    for (i, c) in value.char_indices() {
+        if let Some(escaped) = escape(c) {
+            writer.write_all(&value.as_bytes()[start..i])?;
+            writer.write_all(escaped.as_bytes())?;
+            start = i + c.len_utf8();
+        }
+    }
+
+    writer.write_all(&value.as_bytes()[start..])
+}
+
+fn needs_escape(c: char) -> bool {
+    escape(c).is_some()
+}
+
+fn escape(c: char) -> Option<&'static str> {
+    match c {
+        ';' => Some("\\:"),
+        ' ' => Some("\\s"),
+        '\\' => Some("\\\\"),
+        '\r' => Some("\\r"),
+        '\n' => Some("\\n"),
+        _ => None,
+    }
+}
@@ -9,6 +9,12 @@
 //!
 //! For more information, see the [RFC 952](https://datatracker.ietf.org/doc/html/rfc952) \[DNS:4\]
 //!
+//! [`RFC1123HostValidator`] relaxes these rules to RFC 1123 and enforces the DNS length limits,
+//! [`IdnHostValidator`] accepts internationalized hostnames.
+//!
+//! Hosts in IRC message sources are often IP addresses or cloaks, [`IrcHostParser`]
+//! accepts those as well and returns a [`HostKind`].
+//!
 //! # Example
 //! ```
 //! # use ircv3_tags::host::host;
@@ -17,13 +23,20 @@
 //! let (remain, messages) = host(input).unwrap();
 //! assert_eq!(messages, "example.com");
 //! ```
+use std::{
+    borrow::Cow,
+    fmt,
+    hash::{Hash, Hasher},
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
 use nom::IResult;
 
-use crate::{CharValidator, ErrorKind};
+use crate::{error::parse_error, CharValidator, ErrorKind, HostValidator};
 
 /// RFC 952 (host) parser
-pub fn host(input: &str) -> IResult<&str, &str> {
-    try_host(input).map_err(|err| err.map(|e| nom::error::Error::new(e.input, e.code)))
+pub fn host(input: &str) -> IResult<&str, Host<'_>> {
+    try_host(input).map_err(|err| err.map(|e| nom::error::Error::new(e.input, e.error.nom_code())))
 }
 
 /// RFC 978 (host) parser with helpful error messages
@@ -40,75 +53,464 @@ pub fn host(input: &str) -> IResult<&str, &str> {
 ///     try_host("invalid-"),
 ///     Err(nom::Err::Error(HostError::new(
 ///         "invalid-",
-///         nom::error::ErrorKind::Char,
 ///         ircv3_tags::ErrorKind::Host,
-///         "label contains an invalid chracter",
+///         "label contains an invalid character",
 ///     )))
 /// );
 /// ```
-pub fn try_host(input: &str) -> IResult<&str, &str, HostError<&str>> {
+pub fn try_host(input: &str) -> IResult<&str, Host<'_>, HostError<&str>> {
     RFC952HostParser::default().try_host(input)
 }
 
-pub struct RFC952HostParser<V: CharValidator> {
-    validator: V,
+/// A hostname borrowed from the input.
+///
+/// Comparing and hashing ignore ASCII case and a trailing dot, so `Example.COM.` equals
+/// `example.com`. Internationalized hosts are compared by their A-labels, see
+/// [`to_ascii`](Self::to_ascii), so `bücher.example` equals `xn--bcher-kva.example`.
+/// Labels are compared whole, `notexample.com` is not a subdomain of `example.com`.
+///
+/// # Examples
+///
+/// ```
+/// use ircv3_tags::host::Host;
+///
+/// let host = Host::new("tmi.Twitch.tv");
+/// assert_eq!(host.labels().collect::<Vec<_>>(), ["tmi", "Twitch", "tv"]);
+/// assert_eq!(host.tld(), Some("tv"));
+/// assert!(host.is_subdomain_of("twitch.tv"));
+/// assert!(host.matches("*.twitch.tv"));
+/// assert!(!Host::new("twitch.tv").matches("*.twitch.tv"));
+/// assert_eq!(host, Host::new("tmi.twitch.tv."));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Host<'a>(&'a str);
+
+impl<'a> Host<'a> {
+    /// Wraps a host without validating it, see [`try_host`] to parse one.
+    pub fn new(host: &'a str) -> Self {
+        Self(host)
+    }
+
+    /// Returns the host as it appears in the input.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    /// Returns the host with Unicode labels converted to punycode A-labels,
+    /// see [`IdnHostValidator::to_ascii`].
+    ///
+    /// ASCII hosts and hosts that fail to convert are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ircv3_tags::host::Host;
+    ///
+    /// assert_eq!(Host::new("bücher.example.").to_ascii(), "xn--bcher-kva.example.");
+    /// assert_eq!(Host::new("Example.com").to_ascii(), "Example.com");
+    /// ```
+    pub fn to_ascii(&self) -> Cow<'a, str> {
+        match self.ascii_name() {
+            Cow::Owned(name) if self.0.ends_with('.') => Cow::Owned(name + "."),
+            Cow::Owned(name) => Cow::Owned(name),
+            Cow::Borrowed(_) => Cow::Borrowed(self.0),
+        }
+    }
+
+    /// Returns an iterator over the labels, without a trailing dot.
+    pub fn labels(&self) -> impl DoubleEndedIterator<Item = &'a str> {
+        self.name().split('.')
+    }
+
+    /// Returns the last label, or `None` for a single-label host such as `localhost`.
+    pub fn tld(&self) -> Option<&'a str> {
+        let mut labels = self.labels();
+        let tld = labels.next_back();
+        labels.next_back().and(tld)
+    }
+
+    /// Returns `true` if the host ends with every label of `suffix`, including
+    /// when both are the same host.
+    pub fn has_suffix(&self, suffix: &str) -> bool {
+        let host = self.ascii_name();
+        let mut labels = host.split('.').rev();
+        Host(suffix)
+            .ascii_name()
+            .split('.')
+            .rev()
+            .all(|label| labels.next().is_some_and(|l| l.eq_ignore_ascii_case(label)))
+    }
+
+    /// Returns `true` if the host is a strict subdomain of `parent`.
+    pub fn is_subdomain_of(&self, parent: &str) -> bool {
+        self.labels().count() > Host(parent).labels().count() && self.has_suffix(parent)
+    }
+
+    /// Matches the host against a pattern, either a host or `*.` followed by a host
+    /// to match any of its subdomains.
+    pub fn matches(&self, pattern: &str) -> bool {
+        match pattern.strip_prefix("*.") {
+            Some(parent) => self.is_subdomain_of(parent),
+            None => *self == Host(pattern),
+        }
+    }
+
+    /// The host without a trailing dot.
+    fn name(&self) -> &'a str {
+        self.0.strip_suffix('.').unwrap_or(self.0)
+    }
+
+    /// The host without a trailing dot and with A-labels, used to compare hosts.
+    fn ascii_name(&self) -> Cow<'a, str> {
+        let name = self.name();
+        if name.is_ascii() {
+            return Cow::Borrowed(name);
+        }
+
+        match IdnHostValidator.to_ascii(name) {
+            Ok(name) => Cow::Owned(name),
+            Err(_) => Cow::Borrowed(name),
+        }
+    }
 }
 
-impl Default for RFC952HostParser<StandardHostValidator> {
+impl PartialEq for Host<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ascii_name().eq_ignore_ascii_case(&other.ascii_name())
+    }
+}
+
+impl Eq for Host<'_> {}
+
+impl PartialEq<str> for Host<'_> {
+    fn eq(&self, other: &str) -> bool {
+        *self == Host(other)
+    }
+}
+
+impl PartialEq<&str> for Host<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        *self == Host(other)
+    }
+}
+
+impl Hash for Host<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for b in self.ascii_name().bytes() {
+            state.write_u8(b.to_ascii_lowercase());
+        }
+        state.write_u8(0xff);
+    }
+}
+
+impl AsRef<str> for Host<'_> {
+    fn as_ref(&self) -> &str {
+        self.0
+    }
+}
+
+impl fmt::Display for Host<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+/// Host parser accepting the hosts seen in IRC message sources
+///
+/// # Example
+/// ```
+/// # use ircv3_tags::host::{try_irc_host, HostKind};
+///
+/// let (_, host) = try_irc_host("192.0.2.1").unwrap();
+/// assert_eq!(host, HostKind::Ipv4("192.0.2.1".parse().unwrap()));
+///
+/// let (_, host) = try_irc_host("0::1").unwrap();
+/// assert_eq!(host, HostKind::Ipv6("::1".parse().unwrap()));
+///
+/// let (_, host) = try_irc_host("gateway/web/irccloud.com/x-abc").unwrap();
+/// assert_eq!(host, HostKind::Cloak("gateway/web/irccloud.com/x-abc"));
+///
+/// let (_, host) = try_irc_host("example.com").unwrap();
+/// assert_eq!(host, HostKind::Hostname("example.com"));
+/// ```
+pub fn try_irc_host(input: &str) -> IResult<&str, HostKind<'_>, HostError<&str>> {
+    IrcHostParser::default().try_host(input)
+}
+
+/// IRC host parser
+pub fn irc_host(input: &str) -> IResult<&str, HostKind<'_>> {
+    try_irc_host(input)
+        .map_err(|err| err.map(|e| nom::error::Error::new(e.input, e.error.nom_code())))
+}
+
+/// A host as it appears in an IRC message source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostKind<'a> {
+    /// An RFC 952 hostname, e.g. `irc.example.com`
+    Hostname(&'a str),
+    /// An IPv4 literal, e.g. `192.0.2.1`
+    Ipv4(Ipv4Addr),
+    /// An IPv6 literal, e.g. `2001:db8::1` or `0::1`
+    Ipv6(Ipv6Addr),
+    /// A cloak made of segments, e.g. `user/foo/bar`
+    Cloak(&'a str),
+}
+
+/// Syntax of cloaked hosts: segments separated by `separator`.
+///
+/// Segments may contain ASCII letters, digits, `-`, `.`, `_` and any extra characters.
+#[derive(Debug, Clone)]
+pub struct CloakSyntax {
+    separator: char,
+    extra_chars: Vec<char>,
+}
+
+impl Default for CloakSyntax {
     fn default() -> Self {
         Self {
-            validator: StandardHostValidator,
+            separator: '/',
+            extra_chars: Vec::new(),
         }
     }
 }
 
-impl<V: CharValidator> RFC952HostParser<V> {
-    pub fn new(validator: V) -> Self {
-        Self { validator }
+impl CloakSyntax {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn separator(mut self, separator: char) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    pub fn allow_chars(mut self, chars: &[char]) -> Self {
+        self.extra_chars.extend_from_slice(chars);
+        self
+    }
+
+    fn is_valid_char(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_') || self.extra_chars.contains(&c)
     }
 
-    pub fn try_host<'a>(&self, input: &'a str) -> IResult<&'a str, &'a str, HostError<&'a str>> {
-        let (remain, label_str) = self.label(input)?;
+    fn try_cloak<'a>(&self, input: &'a str) -> Result<&'a str, nom::Err<HostError<&'a str>>> {
+        let mut offset = 0;
+
+        for segment in input.split(self.separator) {
+            if segment.is_empty() {
+                return Err(nom::Err::Error(HostError::new(
+                    &input[offset..],
+                    ErrorKind::Empty,
+                    "cloak segment must not be empty",
+                )));
+            }
+
+            if let Some(position) = segment.find(|c| !self.is_valid_char(c)) {
+                return Err(nom::Err::Error(HostError::new(
+                    &input[offset + position..],
+                    ErrorKind::Host,
+                    "cloak contains an invalid character",
+                )));
+            }
+
+            offset += segment.len() + self.separator.len_utf8();
+        }
+
+        Ok(input)
+    }
+}
+
+/// Host parser for IRC message sources.
+///
+/// Accepts IPv4 and IPv6 literals, cloaks (see [`CloakSyntax`]) and hostnames
+/// validated by [`RFC952HostParser`]. The host ends at a space, CR, LF or NUL.
+///
+/// By default hostnames follow [`RFC1123HostValidator`], so reverse DNS names with
+/// labels starting with a digit, e.g. `12-34-56-78.dyn.example.net`, are accepted.
+pub struct IrcHostParser<V: HostValidator> {
+    hostname_parser: RFC952HostParser<V>,
+    cloak: Option<CloakSyntax>,
+}
+
+impl Default for IrcHostParser<RFC1123HostValidator> {
+    fn default() -> Self {
+        Self::new(RFC952HostParser::new(RFC1123HostValidator))
+    }
+}
+
+impl<V: HostValidator> IrcHostParser<V> {
+    pub fn new(hostname_parser: RFC952HostParser<V>) -> Self {
+        Self {
+            hostname_parser,
+            cloak: Some(CloakSyntax::default()),
+        }
+    }
+
+    /// Sets the cloak syntax, `None` rejects cloaks.
+    pub fn cloak(mut self, cloak: Option<CloakSyntax>) -> Self {
+        self.cloak = cloak;
+        self
+    }
+
+    pub fn hostname_parser(&self) -> &RFC952HostParser<V> {
+        &self.hostname_parser
+    }
+
+    pub fn try_host<'a>(
+        &self,
+        input: &'a str,
+    ) -> IResult<&'a str, HostKind<'a>, HostError<&'a str>> {
+        let end = input.find([' ', '\r', '\n', '\0']).unwrap_or(input.len());
+        let (host, remain) = input.split_at(end);
+
+        if let Ok(ip) = host.parse::<Ipv4Addr>() {
+            return Ok((remain, HostKind::Ipv4(ip)));
+        }
+
+        if let Ok(ip) = host.parse::<Ipv6Addr>() {
+            return Ok((remain, HostKind::Ipv6(ip)));
+        }
 
-        if self.validator.is_invalid_char(label_str) {
+        if let Some(cloak) = &self.cloak {
+            if host.contains(cloak.separator) {
+                return cloak
+                    .try_cloak(host)
+                    .map(|host| (remain, HostKind::Cloak(host)));
+            }
+        }
+
+        if host.contains(':') {
             return Err(nom::Err::Error(HostError::new(
                 input,
-                nom::error::ErrorKind::Char,
                 ErrorKind::Host,
-                "label contains an invalid chracter",
+                "invalid IPv6 address",
             )));
         }
 
-        if remain.starts_with('.') {
-            let mut current_input = remain;
-            let mut position = label_str.len();
+        let hostname = self.hostname(host)?;
+        Ok((remain, HostKind::Hostname(hostname.as_str())))
+    }
+
+    /// Parses a server name spanning the whole input, an IPv4 literal or a hostname
+    /// checked like the hostnames of [`try_host`](Self::try_host).
+    pub(crate) fn try_server_name<'a>(
+        &self,
+        input: &'a str,
+    ) -> Result<&'a str, nom::Err<HostError<&'a str>>> {
+        if input.parse::<Ipv4Addr>().is_ok() {
+            return Ok(input);
+        }
 
-            while let Some(remain2) = current_input.strip_prefix('.') {
-                let (remain2, label_str2) = self.label(remain2)?;
+        self.hostname(input).map(|hostname| hostname.as_str())
+    }
 
-                if self.validator.is_invalid_char(label_str2) {
-                    return Err(nom::Err::Error(HostError::new(
-                        input,
-                        nom::error::ErrorKind::Char,
-                        ErrorKind::Host,
-                        "label contains an invalid chracter",
-                    )));
-                }
+    /// Parses a hostname that must span the whole input.
+    fn hostname<'a>(&self, input: &'a str) -> Result<Host<'a>, nom::Err<HostError<&'a str>>> {
+        let (rest, hostname) = self.hostname_parser.try_host(input)?;
+        if !rest.is_empty() {
+            return Err(nom::Err::Error(HostError::new(
+                rest,
+                ErrorKind::Host,
+                "host contains an invalid character",
+            )));
+        }
+
+        // RFC 1123 section 2.1: a hostname never has the dotted-decimal form, the
+        // top-level label is alphabetic, so this is a malformed IPv4 address.
+        if hostname
+            .tld()
+            .is_some_and(|tld| tld.bytes().all(|b| b.is_ascii_digit()))
+        {
+            return Err(nom::Err::Error(HostError::new(
+                input,
+                ErrorKind::Host,
+                "invalid IPv4 address",
+            )));
+        }
+
+        Ok(hostname)
+    }
+}
+
+pub struct RFC952HostParser<V: HostValidator> {
+    validator: V,
+}
+
+impl Default for RFC952HostParser<StandardHostValidator> {
+    fn default() -> Self {
+        Self {
+            validator: StandardHostValidator,
+        }
+    }
+}
+
+impl<V: HostValidator> RFC952HostParser<V> {
+    pub fn new(validator: V) -> Self {
+        Self { validator }
+    }
+
+    pub fn try_host<'a>(&self, input: &'a str) -> IResult<&'a str, Host<'a>, HostError<&'a str>> {
+        let mut remain = input;
 
-                current_input = remain2;
-                position += label_str2.len() + 1;
+        loop {
+            let (rest, label) = self.label(remain)?;
+
+            if self.validator.is_invalid_char(label) {
+                return Err(nom::Err::Error(HostError::new(
+                    input,
+                    ErrorKind::Host,
+                    "label contains an invalid character",
+                )));
+            }
+
+            if self
+                .validator
+                .max_label_len()
+                .is_some_and(|max| label.len() > max)
+            {
+                return Err(nom::Err::Error(HostError::new(
+                    remain,
+                    ErrorKind::Host,
+                    "label exceeds the maximum length",
+                )));
             }
-            Ok((current_input, &input[0..position]))
-        } else {
-            Ok((remain, label_str))
+
+            remain = rest;
+            match rest.strip_prefix('.') {
+                Some(next)
+                    if self.validator.allow_trailing_dot()
+                        && !next.starts_with(|c| c == '.' || self.validator.is_valid_char(c)) =>
+                {
+                    remain = next;
+                    break;
+                }
+                Some(next) => remain = next,
+                None => break,
+            }
+        }
+
+        let host = &input[..input.len() - remain.len()];
+        let name = host.strip_suffix('.').unwrap_or(host);
+
+        if self
+            .validator
+            .max_host_len()
+            .is_some_and(|max| name.len() > max)
+        {
+            return Err(nom::Err::Error(HostError::new(
+                input,
+                ErrorKind::Host,
+                "host exceeds the maximum length",
+            )));
         }
+
+        Ok((remain, Host(host)))
     }
 
     fn label<'a>(&self, input: &'a str) -> IResult<&'a str, &'a str, HostError<&'a str>> {
         if input.is_empty() {
             return Err(nom::Err::Error(HostError::new(
                 input,
-                nom::error::ErrorKind::Char,
                 ErrorKind::Empty,
                 "label must not be empty",
             )));
@@ -118,7 +520,6 @@ impl<V: CharValidator> RFC952HostParser<V> {
         if !self.validator.is_valid_start_char(first_char) {
             return Err(nom::Err::Error(HostError::new(
                 input,
-                nom::error::ErrorKind::Char,
                 ErrorKind::Host,
                 "label must start with an allowed character",
             )));
@@ -129,7 +530,6 @@ impl<V: CharValidator> RFC952HostParser<V> {
         if label.is_empty() {
             return Err(nom::Err::Error(HostError::new(
                 input,
-                nom::error::ErrorKind::Char,
                 ErrorKind::Empty,
                 "label must not be empty",
             )));
@@ -152,6 +552,8 @@ impl StandardHostValidator {
     /// - Must consist of one or more segments separated by dots
     /// - Each segment must contain only alphanumeric characters and hyphens
     /// - No segment can start or end with a hyphen
+    /// - No segment can contain consecutive hyphens, except after the `xn--` prefix of
+    ///   an A-label, so internationalized vendors written as A-labels parse back
     /// - No segment can be empty
     ///
     /// # Examples
@@ -163,6 +565,8 @@ impl StandardHostValidator {
     /// assert!(standard_host_validate("sub.example.com"));
     /// assert!(!standard_host_validate("example-.com"));
     /// assert!(!standard_host_validate("example..com"));
+    /// assert!(standard_host_validate("xn--bcher-kva.example"));
+    /// assert!(!standard_host_validate("ab--c.example"));
     /// ```
     pub fn validate_host(&self, input: &str) -> bool {
         if input.is_empty()
@@ -188,45 +592,167 @@ impl CharValidator for StandardHostValidator {
         c.is_ascii_alphabetic()
     }
     fn is_invalid_char(&self, s: &str) -> bool {
-        s.contains('_') || s.ends_with('-') || s.contains("--")
+        s.split('.').any(|label| {
+            // The "xn--" prefix of an A-label, e.g. `xn--bcher-kva`, is the only allowed "--"
+            let rest = match label.get(..4) {
+                Some(prefix) if prefix.eq_ignore_ascii_case("xn--") => &label[4..],
+                _ => label,
+            };
+            label.contains('_') || label.ends_with('-') || rest.contains("--")
+        })
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct HostError<I> {
-    pub input: I,
-    pub code: nom::error::ErrorKind,
-    pub error: ErrorKind,
-    pub reason: &'static str,
+impl HostValidator for StandardHostValidator {}
+
+/// Hostname rules from RFC 1123 section 2.1.
+///
+/// Relaxes [`StandardHostValidator`] in the ways RFC 1123 allows:
+/// - Labels may start with a digit, e.g. `3com.com`
+/// - Consecutive hyphens are allowed, e.g. `xn--bcher-kva.example`
+/// - A trailing dot is accepted, e.g. `example.com.`
+///
+/// and enforces the DNS length limits:
+/// - A label is at most 63 bytes
+/// - A host is at most 253 bytes, not counting a trailing dot
+///
+/// # Examples
+///
+/// ```
+/// use ircv3_tags::host::{RFC1123HostValidator, RFC952HostParser};
+///
+/// let parser = RFC952HostParser::new(RFC1123HostValidator);
+/// let (_, host) = parser.try_host("3com.com").unwrap();
+/// assert_eq!(host, "3com.com");
+/// let (remain, host) = parser.try_host("example.com. rest").unwrap();
+/// assert_eq!((remain, host.as_str()), (" rest", "example.com."));
+///
+/// let label = "a".repeat(64);
+/// let err = parser.try_host(&label).unwrap_err();
+/// assert!(matches!(err, nom::Err::Error(e) if e.reason == "label exceeds the maximum length"));
+///
+/// assert!(RFC1123HostValidator.validate_host("1password.com"));
+/// assert!(!RFC1123HostValidator.validate_host("-example.com"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RFC1123HostValidator;
+
+impl RFC1123HostValidator {
+    /// Maximum length in bytes of a single label.
+    pub const MAX_LABEL_LEN: usize = 63;
+    /// Maximum length in bytes of a host, not counting a trailing dot.
+    pub const MAX_HOST_LEN: usize = 253;
+
+    /// Returns `true` if the whole input is a valid RFC 1123 hostname.
+    pub fn validate_host(&self, input: &str) -> bool {
+        matches!(
+            RFC952HostParser::new(self.clone()).try_host(input),
+            Ok(("", _))
+        )
+    }
 }
 
-impl<I> HostError<I> {
-    pub fn new(
-        input: I,
-        code: nom::error::ErrorKind,
-        error: ErrorKind,
-        reason: &'static str,
-    ) -> Self {
-        Self {
-            input,
-            code,
-            error,
-            reason,
-        }
+impl CharValidator for RFC1123HostValidator {
+    fn is_valid_char(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '-'
+    }
+    fn is_valid_start_char(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric()
+    }
+    fn is_invalid_char(&self, s: &str) -> bool {
+        s.ends_with('-')
     }
 }
 
-impl<I> nom::error::ParseError<I> for HostError<I> {
-    fn from_error_kind(input: I, kind: nom::error::ErrorKind) -> Self {
-        HostError {
-            input,
-            code: kind,
-            error: ErrorKind::NomError,
-            reason: "failed to parse host",
-        }
+impl HostValidator for RFC1123HostValidator {
+    fn max_label_len(&self) -> Option<usize> {
+        Some(Self::MAX_LABEL_LEN)
+    }
+    fn max_host_len(&self) -> Option<usize> {
+        Some(Self::MAX_HOST_LEN)
     }
+    fn allow_trailing_dot(&self) -> bool {
+        true
+    }
+}
 
-    fn append(_: I, _: nom::error::ErrorKind, other: Self) -> Self {
-        other
+/// Internationalized hostnames (IDNA 2008 / UTS #46).
+///
+/// Labels may contain Unicode letters, digits and combining marks, e.g. `bücher.example`
+/// or its decomposed form `bu\u{308}cher.example`. Every label must convert to a valid
+/// A-label, so the host is also valid once written as punycode.
+/// Use [`to_ascii`](Self::to_ascii) to compare or serialize a host and
+/// [`to_unicode`](Self::to_unicode) to display it.
+///
+/// # Examples
+///
+/// ```
+/// use ircv3_tags::host::{IdnHostValidator, RFC952HostParser};
+///
+/// let parser = RFC952HostParser::new(IdnHostValidator);
+/// let (_, host) = parser.try_host("bücher.example").unwrap();
+/// assert_eq!(host, "bücher.example");
+///
+/// assert_eq!(
+///     IdnHostValidator.to_ascii("Bücher.example").unwrap(),
+///     "xn--bcher-kva.example"
+/// );
+/// assert_eq!(IdnHostValidator.to_unicode("xn--bcher-kva.example"), "bücher.example");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IdnHostValidator;
+
+impl IdnHostValidator {
+    /// Returns `true` if the whole input is a valid internationalized hostname.
+    pub fn validate_host(&self, input: &str) -> bool {
+        matches!(
+            RFC952HostParser::new(self.clone()).try_host(input),
+            Ok(("", _))
+        ) && idna::domain_to_ascii_strict(input).is_ok()
+    }
+
+    /// Converts a host to its ASCII form, lowercased with Unicode labels as punycode A-labels.
+    ///
+    /// Two hosts are the same if their ASCII forms are equal.
+    pub fn to_ascii<'a>(&self, input: &'a str) -> Result<String, HostError<&'a str>> {
+        idna::domain_to_ascii_strict(input)
+            .map_err(|_| HostError::new(input, ErrorKind::Host, "invalid internationalized host"))
+    }
+
+    /// Converts a host to its Unicode display form, decoding punycode A-labels.
+    ///
+    /// Labels that fail to decode are replaced with U+FFFD.
+    pub fn to_unicode(&self, input: &str) -> String {
+        idna::domain_to_unicode(input).0
     }
 }
+
+impl CharValidator for IdnHostValidator {
+    /// Any non-ASCII character that is not a space or control character, e.g. a
+    /// combining mark, so that IDNA decides whether the label is valid.
+    fn is_valid_char(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric()
+            || c == '-'
+            || (!c.is_ascii() && !c.is_whitespace() && !c.is_control())
+    }
+    fn is_valid_start_char(&self, c: char) -> bool {
+        c.is_alphanumeric()
+    }
+    /// A label must convert to a single A-label, e.g. `a\u{3002}b` maps to two labels.
+    fn is_invalid_char(&self, s: &str) -> bool {
+        s.ends_with('-')
+            || idna::domain_to_ascii_strict(s).map_or(true, |label| label.contains('.'))
+    }
+}
+
+impl HostValidator for IdnHostValidator {}
+
+/// Error returned by the host parsers.
+#[derive(Debug)]
+pub struct HostError<I> {
+    pub input: I,
+    pub error: ErrorKind,
+    pub reason: &'static str,
+}
+
+parse_error!(HostError, "host");
@@ -8,222 +8,461 @@
     since = "3.0.0",
     note = "This crate is deprecated. Use `ircv3_parse` instead."
 )]
-// use std::{collections::HashMap, slice::Iter};
-//
-// use host::StandardHostValidator;
-// use nom::IResult;
-// use tags::{CustomTagNameValidator, IRCv3TagsParser};
-//
-// pub mod host;
-// pub mod tags;
-//
-// mod error;
-// mod traits;
-// mod unescaped_to_escaped;
-//
-// pub use error::{ErrorKind, IRCv3TagsError};
-// pub use traits::CharValidator;
-// pub use unescaped_to_escaped::unescaped_to_escaped;
-//
-// /// Parses only the tags portion of an IRC message, using an unwrapping fallback for errors
-// /// ['@' <tags> <SPACE>]
-// ///
-// /// # Examples
-// ///
-// /// ```
-// /// let input = "@id=123;time=2020-01-01T00:00:00Z :nick!user@host PRIVMSG #channel :Hello";
-// /// let (remain, tags) = ircv3_tags::parse(input);
-// ///
-// /// assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Hello");
-// /// assert_eq!(tags.get("id"), Some("123"));
-// /// ```
-// pub fn parse(input: &str) -> (&str, IRCv3Tags<'_>) {
-//     try_parse(input).unwrap()
-// }
-//
-// /// Safely tries to parse IRC message tags.
-// /// ['@' <tags> <SPACE>]
-// ///
-// /// Similar to `parse`, but returns a `Result` instead of unwrapping.
-// ///
-// /// # Examples
-// /// ```
-// /// let input = "@id=123 :nick!user@host PRIVMSG #channel :Hello";
-// /// let result = ircv3_tags::try_parse(input);
-// /// assert!(result.is_ok());
-// /// ```
-// pub fn try_parse(input: &str) -> IResult<&str, IRCv3Tags<'_>> {
-//     debug_parse(input).map_err(|err| err.map(|e| nom::error::Error::new(e.input, e.code)))
-// }
-//
-// /// Parse to IRCv2 Message tags with helpful error messages
-// pub fn debug_parse(input: &str) -> IResult<&str, IRCv3Tags<'_>, IRCv3TagsError<&str>> {
-//     // Use the default parser
-//     IRCv3TagsParser::default().debug_parse(input)
-// }
-//
-// /// Create a parser allowing underscore characters in tag names
-// ///
-// /// # Examples
-// ///
-// /// ```
-// /// let input = "@tag_name=value :nick!user@host PRIVMSG #channel :Hello";
-// /// let parser = ircv3_tags::with_underscore();
-// /// let (remain, tags) = parser.parse(input);
-// ///
-// /// assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Hello");
-// /// assert_eq!(tags.get("tag_name"), Some("value"));
-// /// ```
-// pub fn with_underscore() -> IRCv3TagsParser<CustomTagNameValidator, StandardHostValidator> {
-//     IRCv3TagsParser::new(CustomTagNameValidator::new().allow_chars(&['_']))
-// }
-//
-// /// Create a custom parser with flexible character validation
-// ///
-// /// # Examples
-// ///
-// /// ```
-// /// # use ircv3_tags::tags::IRCv3TagsParser;
-// ///
-// /// let input = "@tag$name=value :nick!user@host PRIVMSG #channel :Hello";
-// /// let parser = IRCv3TagsParser::new(ircv3_tags::custom_parser().allow_chars(&['_', '$']));
-// /// let (remain, tags) = parser.parse(input);
-// ///
-// /// assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Hello");
-// /// assert_eq!(tags.get("tag$name"), Some("value"));
-// /// ```
-// pub fn custom_parser() -> CustomTagNameValidator {
-//     CustomTagNameValidator::new()
-// }
-//
-// #[derive(Clone, Debug, PartialEq)]
-// pub struct IRCv3Tags<'a>(pub Vec<(&'a str, Option<&'a str>)>);
-//
-// impl<'a> IRCv3Tags<'a> {
-//     /// Gets the raw value for a key in the tag list without unescaping.
-//     ///
-//     /// * `None` if the key doesn't exist
-//     /// * `Some("")` if the key exists with an empty value
-//     /// * `Some(value)` if the key exists with a value
-//     pub fn get(&self, key: &str) -> Option<&'a str> {
-//         self.0.iter().find_map(|(k, v)| {
-//             if *k == key {
-//                 Some(v.unwrap_or(""))
-//             } else {
-//                 None
-//             }
-//         })
-//     }
-//
-//     /// Gets the escaped value for a key in the tag list.
-//     ///
-//     /// This method performs the same lookup as `get()` but also escapes
-//     /// the value according to the IRCv3 tag specification.
-//     ///
-//     /// * `None` if the key doesn't exist
-//     /// * `Some("")` if the key exists with an empty value
-//     /// * `Some(value)` if the key exists with a value (escaped)
-//     ///
-//     /// # Examples
-//     ///
-//     /// ```
-//     /// use ircv3_tags::parse;
-//     ///
-//     /// let input = "@key=value\\:with\\sescapes :nick PRIVMSG #channel :Hello";
-//     /// let (_, tags) = parse(input);
-//     ///
-//     /// assert_eq!(tags.get("key"), Some("value\\:with\\sescapes"));
-//     ///
-//     /// assert_eq!(tags.get_escaped("key"), Some("value;with escapes".to_string()));
-//     /// ```
-//     pub fn get_escaped(&self, key: &str) -> Option<String> {
-//         self.get(key).map(unescaped_to_escaped)
-//     }
-//
-//     /// Converts the tags to a HashMap where empty values are represented as empty strings.
-//     pub fn to_hashmap(&'a self) -> HashMap<&'a str, &'a str> {
-//         self.iter().map(|(k, v)| (*k, v.unwrap_or(""))).collect()
-//     }
-//
-//     /// Consumes the tags and converts them to a HashMap where empty values are represented as empty strings.
-//     pub fn into_hashmap(self) -> HashMap<&'a str, &'a str> {
-//         self.into_iter()
-//             .map(|(k, v)| (k, v.unwrap_or("")))
-//             .collect()
-//     }
-//
-//     /// Converts the tags to a HashMap with escaped values.
-//     pub fn to_hashmap_escaped(&'a self) -> HashMap<&'a str, String> {
-//         self.iter()
-//             .map(|(k, v)| (*k, unescaped_to_escaped(v.unwrap_or(""))))
-//             .collect()
-//     }
-//
-//     /// Consumes the tags and converts them to a HashMap with escaped values.
-//     pub fn into_hashmap_escaped(self) -> HashMap<&'a str, String> {
-//         self.into_iter()
-//             .map(|(k, v)| (k, unescaped_to_escaped(v.unwrap_or(""))))
-//             .collect()
-//     }
-//
-//     /// Converts the tags to an owned HashMap where the keys and values are owned Strings.
-//     pub fn to_map(&self) -> HashMap<String, String> {
-//         self.iter()
-//             .map(|(k, v)| (k.to_string(), v.unwrap_or("").to_string()))
-//             .collect()
-//     }
-//
-//     /// Consumes the tags and converts them to an owned HashMap.
-//     pub fn into_map(self) -> HashMap<String, String> {
-//         self.into_iter()
-//             .map(|(k, v)| (k.to_string(), v.unwrap_or("").to_string()))
-//             .collect()
-//     }
-//
-//     /// Converts the tags to an owned HashMap with escaped values.
-//     pub fn to_map_escaped(&self) -> HashMap<String, String> {
-//         self.iter()
-//             .map(|(k, v)| (k.to_string(), unescaped_to_escaped(v.unwrap_or(""))))
-//             .collect()
-//     }
-//
-//     /// Consumes the tags and converts them to an owned HashMap with escaped values.
-//     pub fn into_map_escaped(self) -> HashMap<String, String> {
-//         self.into_iter()
-//             .map(|(k, v)| (k.to_string(), unescaped_to_escaped(v.unwrap_or(""))))
-//             .collect()
-//     }
-//
-//     pub fn iter(&'a self) -> Iter<'a, (&'a str, Option<&'a str>)> {
-//         self.0.iter()
-//     }
-// }
-//
-// impl<'a> IntoIterator for IRCv3Tags<'a> {
-//     type IntoIter = std::vec::IntoIter<Self::Item>;
-//     type Item = (&'a str, Option<&'a str>);
-//
-//     fn into_iter(self) -> Self::IntoIter {
-//         self.0.into_iter()
-//     }
-// }
-//
-// impl std::fmt::Display for IRCv3Tags<'_> {
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//         let mut iter = self.iter().peekable();
-//         while let Some((key, value)) = iter.next() {
-//             write!(
-//                 f,
-//                 "{}: {}",
-//                 key,
-//                 value
-//                     .as_ref()
-//                     .map_or("''", |v| if v.is_empty() { "''" } else { v })
-//             )?;
-//             if iter.peek().is_some() {
-//                 write!(f, ", ")?;
-//             }
-//         }
-//         Ok(())
-//     }
-// }
+use std::{collections::HashMap, slice::Iter};
+
+use host::StandardHostValidator;
+use nom::IResult;
+use tags::{CustomTagNameValidator, IRCv3TagsParser};
+
+pub mod host;
+pub mod message;
+pub mod source;
+pub mod tags;
+
+mod diagnostic;
+mod error;
+mod escape_value;
+mod serialize;
+mod tag_key;
+mod tag_value;
+mod tags_buf;
+mod traits;
+mod unescaped_to_escaped;
+
+pub use diagnostic::{Diagnostic, Location, Severity};
+pub use error::{ErrorKind, IRCv3TagsError};
+pub use escape_value::{escape_value, escape_value_fmt, escape_value_io};
+pub use serialize::serialize;
+pub use tag_key::TagKey;
+pub use tag_value::{RawValue, TagValue};
+pub use tags_buf::IRCv3TagsBuf;
+pub use traits::{CharValidator, HostValidator};
+pub use unescaped_to_escaped::{
+    unescaped_to_escaped, unescaped_to_escaped_strict, UnescapeMode, UnescapedChars,
+};
+
+/// Parses only the tags portion of an IRC message, never failing
+/// ['@' <tags> <SPACE>]
+///
+/// A line without tags gives empty tags and the whole input, invalid tags are skipped,
+/// see [`IRCv3TagsParser::parse`].
+///
+/// # Examples
+///
+/// ```
+/// let input = "@id=123;time=2020-01-01T00:00:00Z :nick!user@host PRIVMSG #channel :Hello";
+/// let (remain, tags) = ircv3_tags::parse(input);
+///
+/// assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Hello");
+/// assert_eq!(tags.get("id"), Some("123"));
+///
+/// let (remain, tags) = ircv3_tags::parse("PING :server");
+/// assert_eq!(remain, "PING :server");
+/// assert!(tags.0.is_empty());
+/// ```
+pub fn parse(input: &str) -> (&str, IRCv3Tags<'_>) {
+    IRCv3TagsParser::default().parse(input)
+}
+
+/// Strictly parses IRC message tags.
+/// ['@' <tags> <SPACE>]
+///
+/// Unlike [`parse`], which skips invalid tags and treats the tags as optional, any
+/// malformed tag or a line without tags is an error, see [`IRCv3TagsParser::try_parse`].
+///
+/// # Examples
+/// ```
+/// let input = "@id=123 :nick!user@host PRIVMSG #channel :Hello";
+/// let result = ircv3_tags::try_parse(input);
+/// assert!(result.is_ok());
+///
+/// let input = "@id=123;-bad :nick!user@host PRIVMSG #channel :Hello";
+/// assert!(ircv3_tags::try_parse(input).is_err());
+/// assert_eq!(ircv3_tags::parse(input).1.get("id"), Some("123"));
+/// ```
+pub fn try_parse(input: &str) -> IResult<&str, IRCv3Tags<'_>> {
+    debug_parse(input)
+        .map_err(|err| err.map(|e| nom::error::Error::new(e.input, e.error.nom_code())))
+}
+
+/// Tries to parse IRC message tags, treating them as optional.
+/// ['@' <tags> <SPACE>]
+///
+/// A line without tags gives empty tags and the untouched input,
+/// malformed tags are still an error.
+///
+/// # Examples
+/// ```
+/// let input = ":nick!user@host PRIVMSG #channel :Hello";
+/// let (remain, tags) = ircv3_tags::try_parse_optional(input).unwrap();
+/// assert_eq!(remain, input);
+/// assert!(tags.0.is_empty());
+///
+/// assert!(ircv3_tags::try_parse_optional("@=1 :nick!user@host PRIVMSG").is_err());
+/// ```
+pub fn try_parse_optional(input: &str) -> IResult<&str, IRCv3Tags<'_>> {
+    IRCv3TagsParser::default().try_parse_optional(input)
+}
+
+/// Parse optional IRCv3 Message tags with helpful error messages
+pub fn debug_parse_optional(input: &str) -> IResult<&str, IRCv3Tags<'_>, IRCv3TagsError<&str>> {
+    IRCv3TagsParser::default().debug_parse_optional(input)
+}
+
+/// Parse to IRCv2 Message tags with helpful error messages
+pub fn debug_parse(input: &str) -> IResult<&str, IRCv3Tags<'_>, IRCv3TagsError<&str>> {
+    // Use the default parser
+    IRCv3TagsParser::default().debug_parse(input)
+}
+
+/// Create a parser allowing underscore characters in tag names
+///
+/// # Examples
+///
+/// ```
+/// let input = "@tag_name=value :nick!user@host PRIVMSG #channel :Hello";
+/// let parser = ircv3_tags::with_underscore();
+/// let (remain, tags) = parser.parse(input);
+///
+/// assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Hello");
+/// assert_eq!(tags.get("tag_name"), Some("value"));
+/// ```
+pub fn with_underscore() -> IRCv3TagsParser<CustomTagNameValidator, StandardHostValidator> {
+    IRCv3TagsParser::new(CustomTagNameValidator::new().allow_chars(&['_']))
+}
+
+/// Create a custom parser with flexible character validation
+///
+/// # Examples
+///
+/// ```
+/// # use ircv3_tags::tags::IRCv3TagsParser;
+///
+/// let input = "@tag$name=value :nick!user@host PRIVMSG #channel :Hello";
+/// let parser = IRCv3TagsParser::new(ircv3_tags::custom_parser().allow_chars(&['_', '$']));
+/// let (remain, tags) = parser.parse(input);
+///
+/// assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Hello");
+/// assert_eq!(tags.get("tag$name"), Some("value"));
+/// ```
+pub fn custom_parser() -> CustomTagNameValidator {
+    CustomTagNameValidator::new()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IRCv3Tags<'a>(pub Vec<(&'a str, Option<&'a str>)>);
+
+impl<'a> IRCv3Tags<'a> {
+    /// Gets the raw value for a key in the tag list without unescaping.
+    ///
+    /// If the key appears more than once the last value wins, as the specification requires.
+    ///
+    /// * `None` if the key doesn't exist
+    /// * `Some("")` if the key exists with an empty value
+    /// * `Some(value)` if the key exists with a value
+    ///
+    /// An internationalized vendor matches its A-label form, see [`TagKey::to_ascii`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::{host::IdnHostValidator, tags::IRCv3TagsParser};
+    ///
+    /// let parser = IRCv3TagsParser::default().host_validator(IdnHostValidator);
+    /// let (_, tags) = parser.parse("@bücher.example/tag=1 :nick PRIVMSG #channel :Hello");
+    ///
+    /// assert_eq!(tags.get("bücher.example/tag"), Some("1"));
+    /// assert_eq!(tags.get("xn--bcher-kva.example/tag"), Some("1"));
+    /// ```
+    pub fn get<K: AsRef<str>>(&self, key: K) -> Option<&'a str> {
+        self.get_raw(key).map(|value| value.raw().unwrap_or(""))
+    }
+
+    /// Gets the raw value for a key, keeping a tag without a value apart from
+    /// a tag with an empty value.
+    ///
+    /// If the key appears more than once the last value wins, like [`get`](Self::get).
+    ///
+    /// * `None` if the key doesn't exist
+    /// * `Some(RawValue::Absent)` if the key exists without a '=', e.g. `@flag`
+    /// * `Some(RawValue::Empty)` if the key exists with an empty value, e.g. `@flag=`
+    /// * `Some(RawValue::Value(value))` if the key exists with a value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ircv3_tags::RawValue;
+    ///
+    /// let (_, tags) = ircv3_tags::parse("@flag;empty= :nick PRIVMSG #channel :Hello");
+    ///
+    /// assert_eq!(tags.get("flag"), tags.get("empty"));
+    /// assert_eq!(tags.get_raw("flag"), Some(RawValue::Absent));
+    /// assert_eq!(tags.get_raw("empty"), Some(RawValue::Empty));
+    /// ```
+    pub fn get_raw<K: AsRef<str>>(&self, key: K) -> Option<RawValue<'a>> {
+        let key = TagKey::new(key.as_ref()).normalized();
+        self.0
+            .iter()
+            .rev()
+            .find(|(k, _)| tag_key::matches_key(k, &key))
+            .map(|(_, v)| RawValue::from(*v))
+    }
+
+    /// Returns `true` if the key exists, with or without a value.
+    pub fn contains_key<K: AsRef<str>>(&self, key: K) -> bool {
+        let key = TagKey::new(key.as_ref()).normalized();
+        self.0.iter().any(|(k, _)| tag_key::matches_key(k, &key))
+    }
+
+    /// Returns `true` if the key exists with a '=', even if the value is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let (_, tags) = ircv3_tags::parse("@flag;empty=;id=1 :nick PRIVMSG #channel :Hello");
+    ///
+    /// assert!(tags.contains_key("flag"));
+    /// assert!(!tags.has_value("flag"));
+    /// assert!(tags.has_value("empty"));
+    /// assert!(tags.has_value("id"));
+    /// assert!(!tags.has_value("missing"));
+    /// ```
+    pub fn has_value<K: AsRef<str>>(&self, key: K) -> bool {
+        self.get_raw(key).is_some_and(|value| !value.is_absent())
+    }
+
+    /// Gets every raw value for a key in the tag list, in order.
+    ///
+    /// Only parsers using [`DuplicateKeyPolicy::KeepAll`](tags::DuplicateKeyPolicy::KeepAll)
+    /// keep more than one value per key. Tags without a value yield `""`.
+    pub fn get_all<'k, K: AsRef<str> + 'k>(&'k self, key: K) -> impl Iterator<Item = &'a str> + 'k {
+        let key = TagKey::new(key.as_ref()).normalized().into_owned();
+        self.0
+            .iter()
+            .filter(move |(k, _)| tag_key::matches_key(k, &key))
+            .map(|(_, v)| v.unwrap_or(""))
+    }
+
+    /// Gets the escaped value for a key in the tag list.
+    ///
+    /// This method performs the same lookup as `get()` but also escapes
+    /// the value according to the IRCv3 tag specification. Invalid escapes
+    /// drop their backslash and a trailing backslash is removed, see
+    /// [`UnescapeMode::Strict`].
+    ///
+    /// * `None` if the key doesn't exist
+    /// * `Some("")` if the key exists with an empty value
+    /// * `Some(value)` if the key exists with a value (escaped)
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ircv3_tags::parse;
+    ///
+    /// let input = "@key=value\\:with\\sescapes :nick PRIVMSG #channel :Hello";
+    /// let (_, tags) = parse(input);
+    ///
+    /// assert_eq!(tags.get("key"), Some("value\\:with\\sescapes"));
+    ///
+    /// assert_eq!(tags.get_escaped("key"), Some("value;with escapes".to_string()));
+    /// ```
+    pub fn get_escaped<K: AsRef<str>>(&self, key: K) -> Option<String> {
+        self.get_escaped_with(key, UnescapeMode::Strict)
+    }
+
+    /// Gets the escaped value for a key using the given [`UnescapeMode`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ircv3_tags::{parse, UnescapeMode};
+    ///
+    /// let input = "@key=a\\bc\\ :nick PRIVMSG #channel :Hello";
+    /// let (_, tags) = parse(input);
+    ///
+    /// assert_eq!(tags.get_escaped("key"), Some("abc".to_string()));
+    /// assert_eq!(
+    ///     tags.get_escaped_with("key", UnescapeMode::Lenient),
+    ///     Some("a\\bc\\".to_string())
+    /// );
+    /// ```
+    pub fn get_escaped_with<K: AsRef<str>>(&self, key: K, mode: UnescapeMode) -> Option<String> {
+        self.get(key)
+            .map(|v| unescaped_to_escaped::unescape_with_mode(v, mode))
+    }
+
+    /// Gets a lazily unescaped view of the value for a key in the tag list.
+    ///
+    /// Unlike `get_escaped()`, this does not allocate unless the value
+    /// actually contains escape sequences and is unescaped.
+    ///
+    /// * `None` if the key doesn't exist
+    /// * `Some(TagValue(""))` if the key exists with an empty value
+    /// * `Some(value)` if the key exists with a value
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let input = "@key=value\\:with\\sescapes :nick PRIVMSG #channel :Hello";
+    /// let (_, tags) = ircv3_tags::parse(input);
+    ///
+    /// let value = tags.get_value("key").unwrap();
+    /// assert_eq!(value.raw(), "value\\:with\\sescapes");
+    /// assert_eq!(value.unescape(), "value;with escapes");
+    /// ```
+    pub fn get_value<K: AsRef<str>>(&self, key: K) -> Option<TagValue<'a>> {
+        self.get(key).map(TagValue::new)
+    }
+
+    /// Converts the tags to a HashMap where empty values are represented as empty strings.
+    pub fn to_hashmap(&'a self) -> HashMap<&'a str, &'a str> {
+        self.iter().map(|(k, v)| (*k, v.unwrap_or(""))).collect()
+    }
+
+    /// Consumes the tags and converts them to a HashMap where empty values are represented as empty strings.
+    pub fn into_hashmap(self) -> HashMap<&'a str, &'a str> {
+        self.into_iter()
+            .map(|(k, v)| (k, v.unwrap_or("")))
+            .collect()
+    }
+
+    /// Converts the tags to a HashMap with escaped values.
+    pub fn to_hashmap_escaped(&'a self) -> HashMap<&'a str, String> {
+        self.iter()
+            .map(|(k, v)| (*k, unescaped_to_escaped_strict(v.unwrap_or(""))))
+            .collect()
+    }
+
+    /// Converts the tags to a HashMap with lazily unescaped values.
+    pub fn to_hashmap_values(&self) -> HashMap<&'a str, TagValue<'a>> {
+        self.0
+            .iter()
+            .map(|(k, v)| (*k, TagValue::new(v.unwrap_or(""))))
+            .collect()
+    }
+
+    /// Converts the tags to a HashMap with raw values that keep tags without a value
+    /// apart from tags with an empty value, see [`get_raw`](Self::get_raw).
+    pub fn to_hashmap_raw(&self) -> HashMap<&'a str, RawValue<'a>> {
+        self.0
+            .iter()
+            .map(|(k, v)| (*k, RawValue::from(*v)))
+            .collect()
+    }
+
+    /// Consumes the tags and converts them to a HashMap with escaped values.
+    pub fn into_hashmap_escaped(self) -> HashMap<&'a str, String> {
+        self.into_iter()
+            .map(|(k, v)| (k, unescaped_to_escaped_strict(v.unwrap_or(""))))
+            .collect()
+    }
+
+    /// Converts the tags to an owned HashMap where the keys and values are owned Strings.
+    pub fn to_map(&self) -> HashMap<String, String> {
+        self.iter()
+            .map(|(k, v)| (k.to_string(), v.unwrap_or("").to_string()))
+            .collect()
+    }
+
+    /// Consumes the tags and converts them to an owned HashMap.
+    pub fn into_map(self) -> HashMap<String, String> {
+        self.into_iter()
+            .map(|(k, v)| (k.to_string(), v.unwrap_or("").to_string()))
+            .collect()
+    }
+
+    /// Converts the tags to an owned HashMap with escaped values.
+    pub fn to_map_escaped(&self) -> HashMap<String, String> {
+        self.iter()
+            .map(|(k, v)| (k.to_string(), unescaped_to_escaped_strict(v.unwrap_or(""))))
+            .collect()
+    }
+
+    /// Consumes the tags and converts them to an owned HashMap with escaped values.
+    pub fn into_map_escaped(self) -> HashMap<String, String> {
+        self.into_iter()
+            .map(|(k, v)| (k.to_string(), unescaped_to_escaped_strict(v.unwrap_or(""))))
+            .collect()
+    }
+
+    pub fn iter(&'a self) -> Iter<'a, (&'a str, Option<&'a str>)> {
+        self.0.iter()
+    }
+
+    /// Returns an iterator over the keys split into client prefix, vendor and name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let input = "@+example.com/foo=bar;foo=baz :irc.example.com NOTICE #channel :Hello";
+    /// let (_, tags) = ircv3_tags::parse(input);
+    ///
+    /// let vendored: Vec<_> = tags
+    ///     .keys()
+    ///     .filter(|key| key.vendor() == Some("example.com"))
+    ///     .collect();
+    /// assert_eq!(vendored.len(), 1);
+    /// assert_eq!(tags.get(vendored[0]), Some("bar"));
+    /// ```
+    pub fn keys(&self) -> impl Iterator<Item = TagKey<'a>> + '_ {
+        self.0.iter().map(|(k, _)| TagKey::new(k))
+    }
+
+    /// Serializes the tags back into the tags section of an IRC message.
+    /// ['@' <tags> <SPACE>]
+    ///
+    /// Values are kept in the form they were parsed in, which is already escaped,
+    /// so `parse` followed by `to_wire` reproduces the original tags section when its
+    /// tags are valid and its keys are unique. A repeated key is written once, at its
+    /// first position, unless the parser uses
+    /// [`DuplicateKeyPolicy::KeepAll`](tags::DuplicateKeyPolicy::KeepAll).
+    /// An internationalized vendor is written as A-labels, see [`TagKey::to_ascii`].
+    /// Use [`serialize`] to write values that still need escaping.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let input = "@aaa=bbb;ccc;example.com/ddd=e\\:e :nick!ident@host.com PRIVMSG me :Hello";
+    /// let (remain, tags) = ircv3_tags::parse(input);
+    ///
+    /// assert_eq!(tags.to_wire(), "@aaa=bbb;ccc;example.com/ddd=e\\:e ");
+    /// assert_eq!(format!("{}{}", tags.to_wire(), remain), input);
+    /// ```
+    pub fn to_wire(&self) -> String {
+        serialize::serialize_raw(self.0.iter().copied())
+    }
+
+    /// Copies the tags into an owned, mutable [`IRCv3TagsBuf`].
+    pub fn to_buf(&self) -> IRCv3TagsBuf {
+        IRCv3TagsBuf::from(self)
+    }
+}
+
+impl<'a> IntoIterator for IRCv3Tags<'a> {
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+    type Item = (&'a str, Option<&'a str>);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl std::fmt::Display for IRCv3Tags<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut iter = self.iter().peekable();
+        while let Some((key, value)) = iter.next() {
+            write!(
+                f,
+                "{}: {}",
+                key,
+                value
+                    .as_ref()
+                    .map_or("''", |v| if v.is_empty() { "''" } else { v })
+            )?;
+            if iter.peek().is_some() {
+                write!(f, ", ")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,296 @@
+//! IRC message parser following RFC 1459 with IRCv3 message tags.
+//!
+//! ```text
+//! <message>  ::= ['@' <tags> <SPACE>] [':' <prefix> <SPACE>] <command> <params> [<crlf>]
+//! <command>  ::= <letter> { <letter> } | <number> <number> <number>
+//! <params>   ::= <SPACE> [ ':' <trailing> | <middle> <params> ]
+//! ```
+//!
+//! Everything in the parsed [`Message`] borrows from the input.
+//!
+//! # Example
+//! ```
+//! # use ircv3_tags::message::try_message;
+//!
+//! let input = "@id=123 :nick!user@host PRIVMSG #channel :Hello world\r\n";
+//! let (remain, message) = try_message(input).unwrap();
+//!
+//! assert_eq!(remain, "");
+//! assert_eq!(message.tags.get("id"), Some("123"));
+//! assert_eq!(message.source.and_then(|s| s.nick()), Some("nick"));
+//! assert_eq!(message.command, "PRIVMSG");
+//! assert_eq!(message.params, ["#channel"]);
+//! assert_eq!(message.trailing, Some("Hello world"));
+//! ```
+use nom::IResult;
+
+use crate::{
+    error::parse_error,
+    host::{HostError, RFC1123HostValidator, StandardHostValidator},
+    source::{Source, SourceError, SourceParser},
+    tags::{IRCv3TagsParser, StandardTagValidator},
+    CharValidator, ErrorKind, HostValidator, IRCv3Tags, IRCv3TagsError,
+};
+
+/// Maximum number of middle parameters before the rest of the line becomes the trailing parameter.
+const MAX_MIDDLE_PARAMS: usize = 14;
+
+/// A parsed IRC message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message<'a> {
+    /// Message tags, empty when the message has none.
+    pub tags: IRCv3Tags<'a>,
+    /// The prefix without the leading ':'.
+    pub source: Option<Source<'a>>,
+    /// The command, either letters or a three-digit numeric.
+    pub command: &'a str,
+    /// Middle parameters.
+    pub params: Vec<&'a str>,
+    /// The trailing parameter without the leading ':'.
+    pub trailing: Option<&'a str>,
+}
+
+impl<'a> Message<'a> {
+    /// Returns `true` if the command is a three-digit numeric reply.
+    pub fn is_numeric(&self) -> bool {
+        self.command.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    /// Returns an iterator over all parameters, with the trailing parameter last.
+    pub fn all_params(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.params.iter().copied().chain(self.trailing)
+    }
+}
+
+/// IRC message parser
+pub fn message(input: &str) -> IResult<&str, Message<'_>> {
+    try_message(input)
+        .map_err(|err| err.map(|e| nom::error::Error::new(e.input, e.error.nom_code())))
+}
+
+/// IRC message parser with helpful error messages
+pub fn try_message(input: &str) -> IResult<&str, Message<'_>, MessageError<&str>> {
+    MessageParser::default().try_message(input)
+}
+
+/// Parser for whole IRC messages.
+///
+/// `T` validates tag key names, `H` the vendor prefixes of tag keys and `S` the
+/// hostnames in the message source.
+pub struct MessageParser<
+    T: CharValidator,
+    H: HostValidator = StandardHostValidator,
+    S: HostValidator = RFC1123HostValidator,
+> {
+    tags_parser: IRCv3TagsParser<T, H>,
+    source_parser: SourceParser<S>,
+}
+
+impl Default for MessageParser<StandardTagValidator> {
+    fn default() -> Self {
+        Self::new(IRCv3TagsParser::default())
+    }
+}
+
+impl<T: CharValidator, H: HostValidator> MessageParser<T, H> {
+    /// Creates a message parser that parses tags with the given tags parser.
+    pub fn new(tags_parser: IRCv3TagsParser<T, H>) -> Self {
+        Self {
+            tags_parser,
+            source_parser: SourceParser::default(),
+        }
+    }
+}
+
+impl<T, H, S> MessageParser<T, H, S>
+where
+    T: CharValidator,
+    H: HostValidator,
+    S: HostValidator,
+{
+    /// Sets the parser used for the message source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::{
+    /// #     host::{IdnHostValidator, IrcHostParser, RFC952HostParser},
+    /// #     message::MessageParser,
+    /// #     source::SourceParser,
+    /// #     tags::IRCv3TagsParser,
+    /// # };
+    ///
+    /// let hosts = IrcHostParser::new(RFC952HostParser::new(IdnHostValidator));
+    /// let parser = MessageParser::new(IRCv3TagsParser::default().host_validator(IdnHostValidator))
+    ///     .source_parser(SourceParser::new(hosts));
+    ///
+    /// let input = "@bücher.example/id=1 :nick!user@bücher.example PRIVMSG #channel :Hello";
+    /// let (_, message) = parser.try_message(input).unwrap();
+    /// assert_eq!(message.tags.get("bücher.example/id"), Some("1"));
+    /// assert_eq!(message.source.and_then(|s| s.host()), Some("bücher.example"));
+    /// ```
+    pub fn source_parser<V: HostValidator>(
+        self,
+        source_parser: SourceParser<V>,
+    ) -> MessageParser<T, H, V> {
+        MessageParser {
+            tags_parser: self.tags_parser,
+            source_parser,
+        }
+    }
+
+    pub fn try_message<'a>(
+        &self,
+        input: &'a str,
+    ) -> IResult<&'a str, Message<'a>, MessageError<&'a str>> {
+        let (remain, tags) = self
+            .tags_parser
+            .debug_parse_optional(input)
+            .map_err(|err| err.map(MessageError::from))?;
+
+        let (remain, source) = self.source(remain)?;
+        let (remain, command) = self.command(remain)?;
+        let (remain, (params, trailing)) = self.params(remain)?;
+        let remain = line_end(remain);
+
+        Ok((
+            remain,
+            Message {
+                tags,
+                source,
+                command,
+                params,
+                trailing,
+            },
+        ))
+    }
+
+    fn source<'a>(
+        &self,
+        input: &'a str,
+    ) -> IResult<&'a str, Option<Source<'a>>, MessageError<&'a str>> {
+        let Some(rest) = input.strip_prefix(':') else {
+            return Ok((input, None));
+        };
+
+        let (rest, source) = self
+            .source_parser
+            .try_source(rest)
+            .map_err(|err| err.map(MessageError::from))?;
+
+        let remain = rest.trim_start_matches(' ');
+        if remain.len() == rest.len() {
+            return Err(nom::Err::Error(MessageError::new(
+                rest,
+                ErrorKind::Command,
+                "source must be followed by a command",
+            )));
+        }
+
+        Ok((remain, Some(source)))
+    }
+
+    fn command<'a>(&self, input: &'a str) -> IResult<&'a str, &'a str, MessageError<&'a str>> {
+        let end = input.find([' ', '\r', '\n']).unwrap_or(input.len());
+        let command = &input[..end];
+
+        if command.is_empty() {
+            return Err(nom::Err::Error(MessageError::new(
+                input,
+                ErrorKind::Empty,
+                "command must not be empty",
+            )));
+        }
+
+        let is_word = command.bytes().all(|b| b.is_ascii_alphabetic());
+        let is_numeric = command.len() == 3 && command.bytes().all(|b| b.is_ascii_digit());
+        if !is_word && !is_numeric {
+            return Err(nom::Err::Error(MessageError::new(
+                input,
+                ErrorKind::Command,
+                "command must be letters or a three-digit numeric",
+            )));
+        }
+
+        Ok((&input[end..], command))
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn params<'a>(
+        &self,
+        input: &'a str,
+    ) -> IResult<&'a str, (Vec<&'a str>, Option<&'a str>), MessageError<&'a str>> {
+        let mut params = Vec::new();
+        let mut remain = input;
+
+        loop {
+            let rest = remain.trim_start_matches(' ');
+            if rest.len() == remain.len() || rest.is_empty() || rest.starts_with(['\r', '\n']) {
+                return Ok((rest, (params, None)));
+            }
+
+            let end = rest.find(['\r', '\n']).unwrap_or(rest.len());
+            if let Some(trailing) = rest.strip_prefix(':') {
+                return Ok((&trailing[end - 1..], (params, Some(&trailing[..end - 1]))));
+            }
+            if params.len() == MAX_MIDDLE_PARAMS {
+                return Ok((&rest[end..], (params, Some(&rest[..end]))));
+            }
+
+            let end = rest.find([' ', '\r', '\n']).unwrap_or(rest.len());
+            params.push(&rest[..end]);
+            remain = &rest[end..];
+        }
+    }
+}
+
+/// Skips an optional line ending, `\r\n` or a bare `\n`.
+fn line_end(input: &str) -> &str {
+    input
+        .strip_prefix("\r\n")
+        .or_else(|| input.strip_prefix('\n'))
+        .unwrap_or(input)
+}
+
+/// Error returned by the message parser.
+///
+/// Tag and source errors convert into it, keeping the host error of an invalid
+/// vendor prefix or source host as its [`source`](std::error::Error::source).
+#[derive(Debug)]
+pub struct MessageError<I> {
+    pub input: I,
+    pub error: ErrorKind,
+    pub reason: &'static str,
+    host: Option<Box<HostError<I>>>,
+}
+
+impl<I> MessageError<I> {
+    /// Returns the host error of an invalid vendor prefix or source host.
+    pub fn host_error(&self) -> Option<&HostError<I>> {
+        self.host.as_deref()
+    }
+}
+
+parse_error!(MessageError, "IRC message", host);
+
+impl<I> From<IRCv3TagsError<I>> for MessageError<I> {
+    fn from(e: IRCv3TagsError<I>) -> Self {
+        Self {
+            input: e.input,
+            error: e.error,
+            reason: e.reason,
+            host: e.host,
+        }
+    }
+}
+
+impl<I> From<SourceError<I>> for MessageError<I> {
+    fn from(e: SourceError<I>) -> Self {
+        Self {
+            input: e.input,
+            error: e.error,
+            reason: e.reason,
+            host: e.host,
+        }
+    }
+}
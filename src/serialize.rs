@@ -0,0 +1,129 @@
+use crate::{escape_value_fmt, tags::IRCv3TagsParser, ErrorKind, IRCv3TagsError, TagKey};
+
+/// Serializes key/value pairs into the tags section of an IRC message.
+/// ['@' <tags> <SPACE>]
+///
+/// Values are escaped according to the IRCv3 tag specification:
+/// - `;` → `\:`
+/// - ` ` → `\s`
+/// - `\` → `\\`
+/// - CR → `\r`
+/// - LF → `\n`
+///
+/// Tags without a value (`None`) are written without `=`.
+/// An internationalized vendor is written as A-labels, see [`TagKey::to_ascii`].
+/// An empty list of tags produces an empty string.
+///
+/// Keys must be accepted by [`parse`](crate::parse), see
+/// [`IRCv3TagsParser::default`](crate::tags::IRCv3TagsParser::default), and values must
+/// not contain NUL, so the output always parses back with the default parser.
+/// The first invalid key or value is returned as the error.
+///
+/// # Examples
+///
+/// ```
+/// # use ircv3_tags::ErrorKind;
+/// let wire = ircv3_tags::serialize([("id", Some("123")), ("flag", None), ("msg", Some("a b;c"))])
+///     .unwrap();
+/// assert_eq!(wire, "@id=123;flag;msg=a\\sb\\:c ");
+///
+/// let (remain, tags) = ircv3_tags::parse(&wire);
+/// assert_eq!(remain, "");
+/// assert_eq!(tags.get_escaped("msg"), Some("a b;c".to_string()));
+///
+/// let err = ircv3_tags::serialize([("bad key", Some("a"))]).unwrap_err();
+/// assert_eq!(err.error, ErrorKind::InvalidKeyChar);
+/// ```
+pub fn serialize<I, K, V>(tags: I) -> Result<String, IRCv3TagsError<String>>
+where
+    I: IntoIterator<Item = (K, Option<V>)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let tags = tags
+        .into_iter()
+        .map(|(key, value)| {
+            validate_key(key.as_ref())?;
+            if let Some(value) = &value {
+                validate_value(value.as_ref())?;
+            }
+            Ok((key, value))
+        })
+        .collect::<Result<Vec<_>, IRCv3TagsError<String>>>()?;
+
+    Ok(write_tags(tags, |value, out| {
+        // Writing into a String never fails
+        let _ = escape_value_fmt(value, out);
+    }))
+}
+
+/// Writes tags whose values are already in their wire (escaped) form.
+pub(crate) fn serialize_raw<I, K, V>(tags: I) -> String
+where
+    I: IntoIterator<Item = (K, Option<V>)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    write_tags(tags, |value, out| out.push_str(value))
+}
+
+fn write_tags<I, K, V>(tags: I, write_value: impl Fn(&str, &mut String)) -> String
+where
+    I: IntoIterator<Item = (K, Option<V>)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let mut out = String::new();
+
+    for (key, value) in tags {
+        out.push(if out.is_empty() { '@' } else { ';' });
+        out.push_str(&TagKey::new(key.as_ref()).to_ascii());
+        if let Some(value) = value {
+            out.push('=');
+            write_value(value.as_ref(), &mut out);
+        }
+    }
+
+    if !out.is_empty() {
+        out.push(' ');
+    }
+
+    out
+}
+
+/// Checks that the whole key is a tag key [`parse`](crate::parse) accepts, with an
+/// internationalized vendor checked in the A-label form it is written in.
+pub(crate) fn validate_key(key: &str) -> Result<(), IRCv3TagsError<String>> {
+    let parser = IRCv3TagsParser::default();
+    let key = TagKey::new(key).to_ascii();
+
+    let remain = match parser.try_key(&key) {
+        Ok((remain, _)) => remain,
+        // The key parser expects something after the key, e.g. CR or LF are reported as a
+        // missing space, here they are part of the key.
+        Err(nom::Err::Error(err)) if err.error == ErrorKind::MissingSpace => err.input,
+        Err(err) => return Err(IRCv3TagsError::from(err).into_owned()),
+    };
+
+    if remain.is_empty() {
+        Ok(())
+    } else {
+        Err(IRCv3TagsError::new(
+            remain.to_string(),
+            ErrorKind::InvalidKeyChar,
+            "tag key contains an invalid character",
+        ))
+    }
+}
+
+/// Checks that an unescaped value can be escaped, i.e. it does not contain NUL.
+pub(crate) fn validate_value(value: &str) -> Result<(), IRCv3TagsError<String>> {
+    match value.find('\0') {
+        Some(offset) => Err(IRCv3TagsError::new(
+            value[offset..].to_string(),
+            ErrorKind::ForbiddenValueChar,
+            "tag value must not contain NUL",
+        )),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,253 @@
+//! Message source (prefix) parser following RFC 1459.
+//!
+//! ```text
+//! <prefix> ::= <servername> | <nick> [ '!' <user> ] [ '@' <host> ]
+//! ```
+//!
+//! Server names and user hosts are parsed with [`IrcHostParser`], by default following
+//! [`RFC1123HostValidator`] so labels may start with a digit, e.g.
+//! `12-34-56-78.dyn.example.net`. User hosts may also be IP addresses and cloaks.
+//! A bare name containing a '.' is a server name, since nicknames cannot contain one.
+//!
+//! # Example
+//! ```
+//! # use ircv3_tags::source::{try_source, Source};
+//!
+//! let (remain, source) = try_source("nick!ident@host.com PRIVMSG me :Hello").unwrap();
+//! assert_eq!(remain, " PRIVMSG me :Hello");
+//! assert_eq!(source.nick(), Some("nick"));
+//! assert_eq!(source.user(), Some("ident"));
+//! assert_eq!(source.host(), Some("host.com"));
+//!
+//! let (_, source) = try_source("irc.example.com").unwrap();
+//! assert!(source.is_server());
+//! assert_eq!(source, Source::Server("irc.example.com"));
+//! ```
+use std::fmt;
+
+use nom::IResult;
+
+use crate::{
+    error::parse_error,
+    host::{HostError, IrcHostParser, RFC1123HostValidator},
+    ErrorKind, HostValidator,
+};
+
+/// The source of an IRC message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source<'a> {
+    /// A server name, e.g. `irc.example.com`
+    Server(&'a str),
+    /// A user, e.g. `nick!user@host`
+    User {
+        nick: &'a str,
+        user: Option<&'a str>,
+        host: Option<&'a str>,
+    },
+}
+
+impl<'a> Source<'a> {
+    pub fn is_server(&self) -> bool {
+        matches!(self, Self::Server(_))
+    }
+
+    pub fn is_user(&self) -> bool {
+        matches!(self, Self::User { .. })
+    }
+
+    /// Returns the nickname of a user source.
+    pub fn nick(&self) -> Option<&'a str> {
+        match self {
+            Self::Server(_) => None,
+            Self::User { nick, .. } => Some(nick),
+        }
+    }
+
+    /// Returns the username (ident) of a user source.
+    pub fn user(&self) -> Option<&'a str> {
+        match self {
+            Self::Server(_) => None,
+            Self::User { user, .. } => *user,
+        }
+    }
+
+    /// Returns the host of a user source, or the name of a server source.
+    pub fn host(&self) -> Option<&'a str> {
+        match self {
+            Self::Server(name) => Some(name),
+            Self::User { host, .. } => *host,
+        }
+    }
+}
+
+impl fmt::Display for Source<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Server(name) => f.write_str(name),
+            Self::User { nick, user, host } => {
+                f.write_str(nick)?;
+                if let Some(user) = user {
+                    write!(f, "!{}", user)?;
+                }
+                if let Some(host) = host {
+                    write!(f, "@{}", host)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Source parser
+pub fn source(input: &str) -> IResult<&str, Source<'_>> {
+    try_source(input)
+        .map_err(|err| err.map(|e| nom::error::Error::new(e.input, e.error.nom_code())))
+}
+
+/// Source parser with helpful error messages
+///
+/// # Example
+/// ```
+/// # use ircv3_tags::source::{try_source, SourceError};
+///
+/// assert_eq!(
+///     try_source("nick!@host.com"),
+///     Err(nom::Err::Error(SourceError::new(
+///         "@host.com",
+///         ircv3_tags::ErrorKind::Empty,
+///         "user must not be empty",
+///     )))
+/// );
+/// ```
+pub fn try_source(input: &str) -> IResult<&str, Source<'_>, SourceError<&str>> {
+    SourceParser::default().try_source(input)
+}
+
+pub struct SourceParser<H: HostValidator> {
+    host_parser: IrcHostParser<H>,
+}
+
+impl Default for SourceParser<RFC1123HostValidator> {
+    fn default() -> Self {
+        Self {
+            host_parser: IrcHostParser::default(),
+        }
+    }
+}
+
+impl<H: HostValidator> SourceParser<H> {
+    pub fn new(host_parser: IrcHostParser<H>) -> Self {
+        Self { host_parser }
+    }
+
+    /// Parses a source up to the next space or line ending.
+    pub fn try_source<'a>(
+        &self,
+        input: &'a str,
+    ) -> IResult<&'a str, Source<'a>, SourceError<&'a str>> {
+        let end = input.find([' ', '\r', '\n', '\0']).unwrap_or(input.len());
+        let (token, remain) = input.split_at(end);
+
+        if token.is_empty() {
+            return Err(nom::Err::Error(SourceError::new(
+                input,
+                ErrorKind::Empty,
+                "source must not be empty",
+            )));
+        }
+
+        let Some(nick_end) = token.find(['!', '@']) else {
+            if token.contains('.') {
+                let name = self.server_name(token)?;
+                return Ok((remain, Source::Server(name)));
+            }
+
+            return Ok((
+                remain,
+                Source::User {
+                    nick: token,
+                    user: None,
+                    host: None,
+                },
+            ));
+        };
+
+        let (nick, rest) = token.split_at(nick_end);
+        if nick.is_empty() {
+            return Err(nom::Err::Error(SourceError::new(
+                token,
+                ErrorKind::Empty,
+                "nick must not be empty",
+            )));
+        }
+
+        let (user, rest) = match rest.strip_prefix('!') {
+            Some(rest) => {
+                let user_end = rest.find('@').unwrap_or(rest.len());
+                let (user, rest) = rest.split_at(user_end);
+                if user.is_empty() {
+                    return Err(nom::Err::Error(SourceError::new(
+                        rest,
+                        ErrorKind::Empty,
+                        "user must not be empty",
+                    )));
+                }
+                (Some(user), rest)
+            }
+            None => (None, rest),
+        };
+
+        let host = match rest.strip_prefix('@') {
+            Some(host) => {
+                self.host_parser
+                    .try_host(host)
+                    .map_err(|err| err.map(SourceError::from))?;
+                Some(host)
+            }
+            None => None,
+        };
+
+        Ok((remain, Source::User { nick, user, host }))
+    }
+
+    /// Parses a server name that must span the whole input, checked like user hosts
+    /// except that cloaks and IPv6 addresses are not server names.
+    fn server_name<'a>(&self, input: &'a str) -> Result<&'a str, nom::Err<SourceError<&'a str>>> {
+        self.host_parser
+            .try_server_name(input)
+            .map_err(|err| err.map(SourceError::from))
+    }
+}
+
+/// Error returned by the source parser.
+///
+/// When the host is invalid, the [`HostError`] is kept as the
+/// [`source`](std::error::Error::source) of this error.
+#[derive(Debug)]
+pub struct SourceError<I> {
+    pub input: I,
+    pub error: ErrorKind,
+    pub reason: &'static str,
+    pub(crate) host: Option<Box<HostError<I>>>,
+}
+
+impl<I> SourceError<I> {
+    /// Returns the host error of an invalid host.
+    pub fn host_error(&self) -> Option<&HostError<I>> {
+        self.host.as_deref()
+    }
+}
+
+parse_error!(SourceError, "source", host);
+
+/// Wraps the error of an invalid host, keeping its input, kind and reason.
+impl<I: Clone> From<HostError<I>> for SourceError<I> {
+    fn from(e: HostError<I>) -> Self {
+        Self {
+            input: e.input.clone(),
+            error: e.error,
+            reason: e.reason,
+            host: Some(Box::new(e)),
+        }
+    }
+}
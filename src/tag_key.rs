@@ -0,0 +1,251 @@
+use std::{
+    borrow::Cow,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use crate::host::{Host, IdnHostValidator};
+
+/// A tag key split into its parts.
+/// [ <client_prefix> ] [ <vendor> '/' ] <key_name>
+///
+/// # Examples
+///
+/// ```
+/// use ircv3_tags::TagKey;
+///
+/// let key = TagKey::new("+example.com/foo");
+/// assert!(key.is_client_only());
+/// assert_eq!(key.vendor(), Some("example.com"));
+/// assert_eq!(key.name(), "foo");
+/// assert_eq!(key.as_str(), "+example.com/foo");
+///
+/// let key = TagKey::new("foo");
+/// assert!(!key.is_client_only());
+/// assert_eq!(key.vendor(), None);
+/// assert_eq!(key.name(), "foo");
+/// ```
+///
+/// Keys are compared and hashed with the vendor as lowercase A-labels, so a key with an
+/// internationalized vendor equals the same key with the vendor as A-labels, and vendors
+/// compare case-insensitively and without a trailing dot like hostnames. Key names stay
+/// case-sensitive.
+///
+/// ```
+/// use ircv3_tags::TagKey;
+///
+/// assert_eq!(TagKey::new("bücher.example/tag"), TagKey::new("xn--bcher-kva.example/tag"));
+/// assert_eq!(TagKey::new("bücher.example/tag"), "xn--bcher-kva.example/tag");
+/// assert_eq!(TagKey::new("Example.com/tag"), "example.com/tag");
+/// assert_eq!(TagKey::new("example.com./tag"), "example.com/tag");
+/// assert_ne!(TagKey::new("example.com/Tag"), "example.com/tag");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TagKey<'a> {
+    raw: &'a str,
+    client_only: bool,
+    vendor: Option<&'a str>,
+    name: &'a str,
+}
+
+impl<'a> TagKey<'a> {
+    /// Splits an already validated key into its parts.
+    ///
+    /// The vendor is everything between the optional `+` and the first `/`.
+    /// Use [`IRCv3TagsParser::try_key`](crate::tags::IRCv3TagsParser::try_key)
+    /// to validate a key while splitting it.
+    pub fn new(raw: &'a str) -> Self {
+        let (client_only, rest) = match raw.strip_prefix('+') {
+            Some(rest) => (true, rest),
+            None => (false, raw),
+        };
+
+        let (vendor, name) = match rest.split_once('/') {
+            Some((vendor, name)) => (Some(vendor), name),
+            None => (None, rest),
+        };
+
+        Self {
+            raw,
+            client_only,
+            vendor,
+            name,
+        }
+    }
+
+    pub(crate) fn from_parts(
+        raw: &'a str,
+        client_only: bool,
+        vendor: Option<&'a str>,
+        name: &'a str,
+    ) -> Self {
+        Self {
+            raw,
+            client_only,
+            vendor,
+            name,
+        }
+    }
+
+    /// Returns `true` if the key starts with the client prefix `+`.
+    pub fn is_client_only(&self) -> bool {
+        self.client_only
+    }
+
+    /// Returns the vendor prefix without the trailing `/`.
+    pub fn vendor(&self) -> Option<&'a str> {
+        self.vendor
+    }
+
+    /// Returns the vendor prefix as a [`Host`], for matching against vendor allowlists.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ircv3_tags::TagKey;
+    ///
+    /// let key = TagKey::new("Twitch.tv/foo");
+    /// assert!(key.vendor_host().is_some_and(|vendor| vendor == "twitch.tv"));
+    /// assert!(TagKey::new("tmi.twitch.tv/foo").vendor_host().is_some_and(|vendor| vendor.matches("*.twitch.tv")));
+    /// ```
+    pub fn vendor_host(&self) -> Option<Host<'a>> {
+        self.vendor.map(Host::new)
+    }
+
+    /// Returns the key name without client prefix and vendor.
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Returns the full key as it appears on the wire.
+    pub fn as_str(&self) -> &'a str {
+        self.raw
+    }
+
+    /// Returns the key with an internationalized vendor converted to punycode,
+    /// see [`IdnHostValidator::to_ascii`].
+    ///
+    /// ASCII vendors and vendors that fail to convert are returned unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ircv3_tags::TagKey;
+    ///
+    /// let key = TagKey::new("+bücher.example/tag");
+    /// assert_eq!(key.to_ascii(), "+xn--bcher-kva.example/tag");
+    /// assert_eq!(TagKey::new("example.com/tag").to_ascii(), "example.com/tag");
+    /// ```
+    pub fn to_ascii(&self) -> Cow<'a, str> {
+        match self.vendor {
+            Some(vendor) if !vendor.is_ascii() => match IdnHostValidator.to_ascii(vendor) {
+                Ok(vendor) => Cow::Owned(self.with_vendor(&vendor)),
+                Err(_) => Cow::Borrowed(self.raw),
+            },
+            _ => Cow::Borrowed(self.raw),
+        }
+    }
+
+    /// Returns the key as it is compared and hashed, with the vendor as lowercase
+    /// A-labels without a trailing dot, like [`Host`] equality. Only allocates when the
+    /// vendor is internationalized, has uppercase letters or a trailing dot.
+    pub(crate) fn normalized(&self) -> Cow<'a, str> {
+        let Some(vendor) = self.vendor else {
+            return Cow::Borrowed(self.raw);
+        };
+
+        let name = vendor.strip_suffix('.').unwrap_or(vendor);
+        if !vendor.is_ascii() {
+            return match IdnHostValidator.to_ascii(name) {
+                Ok(name) => Cow::Owned(self.with_vendor(&name)),
+                Err(_) => Cow::Owned(self.with_vendor(name)),
+            };
+        }
+
+        if name.len() == vendor.len() && !vendor.bytes().any(|b| b.is_ascii_uppercase()) {
+            return Cow::Borrowed(self.raw);
+        }
+        Cow::Owned(self.with_vendor(&name.to_ascii_lowercase()))
+    }
+
+    /// Returns the key with a punycode vendor converted to its Unicode display form,
+    /// see [`IdnHostValidator::to_unicode`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ircv3_tags::TagKey;
+    ///
+    /// let key = TagKey::new("xn--bcher-kva.example/tag");
+    /// assert_eq!(key.to_unicode(), "bücher.example/tag");
+    /// ```
+    pub fn to_unicode(&self) -> Cow<'a, str> {
+        match self.vendor {
+            Some(vendor) if vendor.split('.').any(is_a_label) => {
+                Cow::Owned(self.with_vendor(&IdnHostValidator.to_unicode(vendor)))
+            }
+            _ => Cow::Borrowed(self.raw),
+        }
+    }
+
+    fn with_vendor(&self, vendor: &str) -> String {
+        let prefix = if self.client_only { "+" } else { "" };
+        format!("{}{}/{}", prefix, vendor, self.name)
+    }
+}
+
+fn is_a_label(label: &str) -> bool {
+    label
+        .get(..4)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("xn--"))
+}
+
+impl AsRef<str> for TagKey<'_> {
+    fn as_ref(&self) -> &str {
+        self.raw
+    }
+}
+
+impl PartialEq for TagKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        matches_key(self.raw, &other.normalized())
+    }
+}
+
+impl Eq for TagKey<'_> {}
+
+impl Hash for TagKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized().hash(state)
+    }
+}
+
+impl PartialEq<str> for TagKey<'_> {
+    fn eq(&self, other: &str) -> bool {
+        *self == TagKey::new(other)
+    }
+}
+
+impl PartialEq<&str> for TagKey<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        *self == TagKey::new(other)
+    }
+}
+
+/// Returns `true` if the stored key `key` equals a key already converted with
+/// [`TagKey::normalized`], only allocating when `key` itself needs converting.
+pub(crate) fn matches_key(key: &str, normalized: &str) -> bool {
+    key == normalized || TagKey::new(key).normalized() == normalized
+}
+
+impl fmt::Display for TagKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.raw)
+    }
+}
+
+impl<'a> From<&'a str> for TagKey<'a> {
+    fn from(raw: &'a str) -> Self {
+        Self::new(raw)
+    }
+}
@@ -0,0 +1,142 @@
+use std::{borrow::Cow, fmt};
+
+use crate::{unescaped_to_escaped::unescape_with_mode, UnescapeMode, UnescapedChars};
+
+/// A borrowed view of a tag value as it appears on the wire.
+///
+/// Unescaping is done lazily, so reading a value that contains no escape
+/// sequences never allocates.
+///
+/// # Examples
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// let (_, tags) = ircv3_tags::parse("@id=123;msg=hello\\sworld :nick PRIVMSG #channel :Hello");
+///
+/// let id = tags.get_value("id").unwrap();
+/// assert!(matches!(id.unescape(), Cow::Borrowed("123")));
+///
+/// let msg = tags.get_value("msg").unwrap();
+/// assert_eq!(msg.raw(), "hello\\sworld");
+/// assert_eq!(msg.unescape(), "hello world");
+/// assert_eq!(msg.to_string(), "hello world");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TagValue<'a>(&'a str);
+
+impl<'a> TagValue<'a> {
+    /// Wraps a raw (still escaped) tag value.
+    pub fn new(raw: &'a str) -> Self {
+        Self(raw)
+    }
+
+    /// Returns the value exactly as it appears on the wire.
+    pub fn raw(&self) -> &'a str {
+        self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns `true` if the value contains escape sequences.
+    pub fn is_escaped(&self) -> bool {
+        self.0.contains('\\')
+    }
+
+    /// Unescapes the value following the specification strictly.
+    ///
+    /// Returns `Cow::Borrowed` when the value contains no escape sequences.
+    pub fn unescape(&self) -> Cow<'a, str> {
+        self.unescape_with(UnescapeMode::Strict)
+    }
+
+    /// Unescapes the value using the given [`UnescapeMode`].
+    pub fn unescape_with(&self, mode: UnescapeMode) -> Cow<'a, str> {
+        if self.is_escaped() {
+            Cow::Owned(unescape_with_mode(self.0, mode))
+        } else {
+            Cow::Borrowed(self.0)
+        }
+    }
+
+    /// Returns an iterator over the strictly unescaped characters of the value.
+    pub fn chars(&self) -> UnescapedChars<'a> {
+        UnescapedChars::with_mode(self.0, UnescapeMode::Strict)
+    }
+}
+
+impl fmt::Display for TagValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.is_escaped() {
+            return f.write_str(self.0);
+        }
+
+        for c in self.chars() {
+            fmt::Write::write_char(f, c)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> From<&'a str> for TagValue<'a> {
+    fn from(raw: &'a str) -> Self {
+        Self(raw)
+    }
+}
+
+/// A raw tag value that keeps a tag without a value apart from a tag with an empty value.
+///
+/// # Examples
+///
+/// ```
+/// use ircv3_tags::RawValue;
+///
+/// let (_, tags) = ircv3_tags::parse("@flag;empty=;id=123 :nick PRIVMSG #channel :Hello");
+///
+/// assert_eq!(tags.get_raw("flag"), Some(RawValue::Absent));
+/// assert_eq!(tags.get_raw("empty"), Some(RawValue::Empty));
+/// assert_eq!(tags.get_raw("id"), Some(RawValue::Value("123")));
+/// assert_eq!(tags.get_raw("missing"), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RawValue<'a> {
+    /// The tag has no '=', e.g. `@flag`
+    Absent,
+    /// The tag has a '=' but no value, e.g. `@flag=`
+    Empty,
+    /// The tag has a non-empty value, still escaped
+    Value(&'a str),
+}
+
+impl<'a> RawValue<'a> {
+    /// Returns the value as stored in [`IRCv3Tags`](crate::IRCv3Tags),
+    /// `None` for [`Absent`](Self::Absent).
+    pub fn raw(&self) -> Option<&'a str> {
+        match self {
+            Self::Absent => None,
+            Self::Empty => Some(""),
+            Self::Value(value) => Some(value),
+        }
+    }
+
+    /// Returns the value as a [`TagValue`], `None` for [`Absent`](Self::Absent).
+    pub fn value(&self) -> Option<TagValue<'a>> {
+        self.raw().map(TagValue::new)
+    }
+
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Self::Absent)
+    }
+}
+
+impl<'a> From<Option<&'a str>> for RawValue<'a> {
+    fn from(raw: Option<&'a str>) -> Self {
+        match raw {
+            None => Self::Absent,
+            Some("") => Self::Empty,
+            Some(value) => Self::Value(value),
+        }
+    }
+}
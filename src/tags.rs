@@ -1,28 +1,86 @@
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    ops::Range,
+};
+
 use nom::{
-    bytes::complete::take_till,
-    character::{char, complete::space1},
-    combinator::{opt, recognize},
-    multi::separated_list1,
-    sequence::{delimited, preceded, terminated},
-    IResult, Parser,
+    bytes::complete::take_till, character::complete::char, combinator::opt, IResult, Parser,
 };
 
 use crate::{
-    host::{RFC952HostParser, StandardHostValidator},
-    CharValidator, ErrorKind, IRCv3Tags, IRCv3TagsError,
+    host::{Host, HostError, RFC952HostParser, StandardHostValidator},
+    CharValidator, Diagnostic, ErrorKind, HostValidator, IRCv3Tags, IRCv3TagsError, Severity,
+    TagKey,
 };
 
-pub struct IRCv3TagsParser<T: CharValidator, H: CharValidator> {
+/// Maximum size in bytes of tag data sent by a client, not including the leading '@' and trailing space.
+///
+/// With them the client tags take at most 4096 bytes, the other half of [`TAG_DATA_LIMIT`].
+pub const CLIENT_TAG_DATA_LIMIT: usize = 4094;
+
+/// Maximum size in bytes of the whole tag data, including the leading '@' and trailing space.
+pub const TAG_DATA_LIMIT: usize = 8191;
+
+pub struct IRCv3TagsParser<T: CharValidator, H: HostValidator> {
     tag_name_validator: T,
     host_validator: RFC952HostParser<H>,
+    client_tag_limit: Option<usize>,
+    total_tag_limit: Option<usize>,
+    duplicate_policy: DuplicateKeyPolicy,
+    max_tags: Option<usize>,
+    reject_invalid_escapes: bool,
+    allow_empty_tags: bool,
+    separator: TagsSeparator,
 }
 
 impl Default for IRCv3TagsParser<StandardTagValidator, StandardHostValidator> {
     fn default() -> Self {
-        Self {
-            tag_name_validator: StandardTagValidator,
-            host_validator: RFC952HostParser::new(StandardHostValidator),
-        }
+        Self::new(StandardTagValidator)
+    }
+}
+
+impl IRCv3TagsParser<StrictTagValidator, StandardHostValidator> {
+    /// Creates a parser that follows the message-tags grammar exactly.
+    ///
+    /// - Key names are ASCII letters, digits and hyphens, see [`StrictTagValidator`]
+    /// - A vendor is an ASCII hostname followed by '/'
+    /// - A '+' is only allowed once, before the vendor
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::tags::IRCv3TagsParser;
+    ///
+    /// let parser = IRCv3TagsParser::strict();
+    ///
+    /// let (_, tags) = parser.parse("@+example.com/1st=a;-x=b :nick PRIVMSG #channel :Hello");
+    /// assert_eq!(tags.get("+example.com/1st"), Some("a"));
+    /// assert_eq!(tags.get("-x"), Some("b"));
+    ///
+    /// assert!(parser.try_parse("@ключ=a :nick PRIVMSG #channel :Hello").is_err());
+    /// ```
+    pub fn strict() -> Self {
+        Self::new(StrictTagValidator)
+    }
+}
+
+impl IRCv3TagsParser<LenientTagValidator, StandardHostValidator> {
+    /// Creates a parser that also accepts non-ASCII letters and digits in key names,
+    /// see [`LenientTagValidator`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::tags::IRCv3TagsParser;
+    ///
+    /// let input = "@k名前=a :nick PRIVMSG #channel :Hello";
+    /// assert!(IRCv3TagsParser::default().try_parse(input).is_err());
+    ///
+    /// let (_, tags) = IRCv3TagsParser::lenient().parse(input);
+    /// assert_eq!(tags.get("k名前"), Some("a"));
+    /// ```
+    pub fn lenient() -> Self {
+        Self::new(LenientTagValidator)
     }
 }
 
@@ -34,16 +92,288 @@ where
         Self {
             tag_name_validator: validator,
             host_validator: RFC952HostParser::new(StandardHostValidator),
+            client_tag_limit: None,
+            total_tag_limit: None,
+            duplicate_policy: DuplicateKeyPolicy::default(),
+            max_tags: None,
+            reject_invalid_escapes: false,
+            allow_empty_tags: false,
+            separator: TagsSeparator::default(),
+        }
+    }
+}
+
+impl<T, H> IRCv3TagsParser<T, H>
+where
+    T: CharValidator,
+    H: HostValidator,
+{
+    /// Sets the validator used for vendor prefixes, e.g. `example.com` in `example.com/foo`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::{host::RFC1123HostValidator, tags::IRCv3TagsParser};
+    ///
+    /// let input = "@1password.com/tag=abc :nick PRIVMSG #channel :Hello";
+    /// assert!(IRCv3TagsParser::default().try_parse(input).is_err());
+    ///
+    /// let parser = IRCv3TagsParser::default().host_validator(RFC1123HostValidator);
+    /// let (_, tags) = parser.parse(input);
+    /// assert_eq!(tags.get("1password.com/tag"), Some("abc"));
+    /// ```
+    pub fn host_validator<V: HostValidator>(self, validator: V) -> IRCv3TagsParser<T, V> {
+        IRCv3TagsParser {
+            tag_name_validator: self.tag_name_validator,
+            host_validator: RFC952HostParser::new(validator),
+            client_tag_limit: self.client_tag_limit,
+            total_tag_limit: self.total_tag_limit,
+            duplicate_policy: self.duplicate_policy,
+            max_tags: self.max_tags,
+            reject_invalid_escapes: self.reject_invalid_escapes,
+            allow_empty_tags: self.allow_empty_tags,
+            separator: self.separator,
         }
     }
 
+    /// Rejects input whose client-only tags (`+` prefixed) take more than `limit` bytes,
+    /// counted as the client-only tags joined with ';', without the '@' and trailing space.
+    pub fn client_tag_limit(mut self, limit: usize) -> Self {
+        self.client_tag_limit = Some(limit);
+        self
+    }
+
+    /// Rejects input whose tag section `'@' <tags> <SPACE>` takes more than `limit` bytes.
+    ///
+    /// Both limits measure the raw section before it is split into tags, so segments
+    /// skipped by [`recover`](Self::recover) count as well.
+    pub fn total_tag_limit(mut self, limit: usize) -> Self {
+        self.total_tag_limit = Some(limit);
+        self
+    }
+
+    /// Enforces the size limits from the message-tags specification,
+    /// [`CLIENT_TAG_DATA_LIMIT`] for client-only tags and [`TAG_DATA_LIMIT`] in total.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::{tags::{IRCv3TagsParser, TagLimit}, ErrorKind};
+    ///
+    /// let parser = IRCv3TagsParser::default().with_spec_limits();
+    /// let input = format!("@+tag={} TAGMSG #channel", "a".repeat(4096));
+    ///
+    /// let Err(nom::Err::Error(err)) = parser.debug_parse(&input) else {
+    ///     panic!("expected an error");
+    /// };
+    /// assert_eq!(err.error, ErrorKind::TooLong(TagLimit::ClientOnly));
+    /// ```
+    pub fn with_spec_limits(self) -> Self {
+        self.client_tag_limit(CLIENT_TAG_DATA_LIMIT)
+            .total_tag_limit(TAG_DATA_LIMIT)
+    }
+
+    /// Sets how repeated tag keys are handled, see [`DuplicateKeyPolicy`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::tags::{DuplicateKeyPolicy, IRCv3TagsParser};
+    ///
+    /// let input = "@a=1;b=2;a=3 :nick PRIVMSG #channel :Hello";
+    ///
+    /// let (_, tags) = IRCv3TagsParser::default().parse(input);
+    /// assert_eq!(tags.get("a"), Some("3"));
+    ///
+    /// let parser = IRCv3TagsParser::default().duplicate_policy(DuplicateKeyPolicy::FirstWins);
+    /// let (_, tags) = parser.parse(input);
+    /// assert_eq!(tags.get("a"), Some("1"));
+    ///
+    /// let parser = IRCv3TagsParser::default().duplicate_policy(DuplicateKeyPolicy::KeepAll);
+    /// let (_, tags) = parser.parse(input);
+    /// assert_eq!(tags.get_all("a").collect::<Vec<_>>(), ["1", "3"]);
+    ///
+    /// let parser = IRCv3TagsParser::default().duplicate_policy(DuplicateKeyPolicy::Reject);
+    /// assert!(parser.try_parse(input).is_err());
+    /// ```
+    pub fn duplicate_policy(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_policy = policy;
+        self
+    }
+
+    /// Rejects input with more than `limit` tags, counting repeated keys.
+    pub fn max_tags(mut self, limit: usize) -> Self {
+        self.max_tags = Some(limit);
+        self
+    }
+
+    /// Rejects tag values with an invalid escape, a backslash followed by anything
+    /// other than `:`, `s`, `\`, `r` or `n`, or a trailing backslash.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::{tags::IRCv3TagsParser, ErrorKind};
+    ///
+    /// let input = "@a=b\\x :nick PRIVMSG #channel :Hello";
+    /// assert!(IRCv3TagsParser::default().try_parse(input).is_ok());
+    ///
+    /// let parser = IRCv3TagsParser::default().reject_invalid_escapes();
+    /// let Err(nom::Err::Error(err)) = parser.debug_parse(input) else {
+    ///     panic!("expected an error");
+    /// };
+    /// assert_eq!(err.error, ErrorKind::InvalidEscape);
+    /// assert_eq!(err.input, "\\x :nick PRIVMSG #channel :Hello");
+    /// ```
+    pub fn reject_invalid_escapes(mut self) -> Self {
+        self.reject_invalid_escapes = true;
+        self
+    }
+
+    /// Accepts and ignores empty tag segments, e.g. `@a=b;;c` or a trailing ';' in `@a=b; `.
+    /// A tags section must still hold at least one tag, `@ ` and `@; ` are rejected.
+    ///
+    /// Each ignored segment is reported as a [`Severity::Warning`] by
+    /// [`debug_parse_with_warnings`](Self::debug_parse_with_warnings) and
+    /// [`recover`](Self::recover).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::tags::IRCv3TagsParser;
+    ///
+    /// let input = "@a=b;;c; :nick PRIVMSG #channel :Hello";
+    /// assert!(IRCv3TagsParser::default().try_parse(input).is_err());
+    ///
+    /// let parser = IRCv3TagsParser::default().allow_empty_tags();
+    /// let (remain, tags) = parser.try_parse(input).unwrap();
+    /// assert_eq!(remain, ":nick PRIVMSG #channel :Hello");
+    /// assert_eq!(tags.0, vec![("a", Some("b")), ("c", None)]);
+    /// ```
+    pub fn allow_empty_tags(mut self) -> Self {
+        self.allow_empty_tags = true;
+        self
+    }
+
+    /// Sets what must follow the tags, see [`TagsSeparator`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::tags::{IRCv3TagsParser, TagsSeparator};
+    ///
+    /// let parser = IRCv3TagsParser::default().separator(TagsSeparator::SpacesOrLineEnd);
+    /// let (remain, tags) = parser.try_parse("@+typing=active\r\n").unwrap();
+    /// assert_eq!(remain, "");
+    /// assert_eq!(tags.get("+typing"), Some("active"));
+    /// ```
+    pub fn separator(mut self, separator: TagsSeparator) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Parses the tags without failing.
+    ///
+    /// - A line without tags gives empty tags and the whole input.
+    /// - Invalid tags are skipped, see [`recover`](Self::recover).
+    /// - When the tags cannot be recovered, e.g. no space follows them or a size limit is
+    ///   exceeded, the result is empty tags and the whole input.
+    ///
+    /// Use [`debug_parse`](Self::debug_parse) to reject malformed tags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::tags::IRCv3TagsParser;
+    ///
+    /// let parser = IRCv3TagsParser::default();
+    ///
+    /// let (remain, tags) = parser.parse(":nick PRIVMSG #channel :Hello");
+    /// assert_eq!(remain, ":nick PRIVMSG #channel :Hello");
+    /// assert!(tags.0.is_empty());
+    ///
+    /// let (remain, tags) = parser.parse("@id=1;-bad :nick PRIVMSG #channel :Hello");
+    /// assert_eq!(remain, ":nick PRIVMSG #channel :Hello");
+    /// assert_eq!(tags.get("id"), Some("1"));
+    ///
+    /// let (remain, tags) = parser.parse("@id=1");
+    /// assert_eq!(remain, "@id=1");
+    /// assert!(tags.0.is_empty());
+    /// ```
     pub fn parse<'a>(&self, input: &'a str) -> (&'a str, IRCv3Tags<'a>) {
-        self.try_parse(input).unwrap()
+        match self.recover(input) {
+            Ok((remain, (tags, _))) => (remain, tags),
+            Err(_) => (input, IRCv3Tags(Vec::new())),
+        }
     }
 
+    /// Parses the tags, failing on the first invalid tag.
+    ///
+    /// Works like [`debug_parse`](Self::debug_parse), with the errors reduced to
+    /// `nom::error::Error`. Unlike [`parse`](Self::parse), a missing '@' is an error,
+    /// see [`try_parse_optional`](Self::try_parse_optional) for optional tags.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::tags::IRCv3TagsParser;
+    ///
+    /// let parser = IRCv3TagsParser::default();
+    ///
+    /// let (remain, tags) = parser.try_parse("@id=1 :nick PRIVMSG #channel :Hello").unwrap();
+    /// assert_eq!(remain, ":nick PRIVMSG #channel :Hello");
+    /// assert_eq!(tags.get("id"), Some("1"));
+    ///
+    /// assert!(parser.try_parse("@id=1;-bad :nick PRIVMSG #channel :Hello").is_err());
+    /// assert!(parser.try_parse(":nick PRIVMSG #channel :Hello").is_err());
+    /// ```
     pub fn try_parse<'a>(&self, input: &'a str) -> IResult<&'a str, IRCv3Tags<'a>> {
         self.debug_parse(input)
-            .map_err(|err| err.map(|e| nom::error::Error::new(e.input, e.code)))
+            .map_err(|err| err.map(|e| nom::error::Error::new(e.input, e.error.nom_code())))
+    }
+
+    /// Like [`try_parse`](Self::try_parse), but a line without tags is not an error,
+    /// see [`debug_parse_optional`](Self::debug_parse_optional).
+    pub fn try_parse_optional<'a>(&self, input: &'a str) -> IResult<&'a str, IRCv3Tags<'a>> {
+        self.debug_parse_optional(input)
+            .map_err(|err| err.map(|e| nom::error::Error::new(e.input, e.error.nom_code())))
+    }
+
+    /// Parses the tags if the line has any.
+    ///
+    /// A line not starting with '@' gives empty tags and the untouched input,
+    /// a line starting with '@' is parsed like [`debug_parse`](Self::debug_parse),
+    /// so malformed tags are still rejected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::{tags::IRCv3TagsParser, ErrorKind};
+    ///
+    /// let parser = IRCv3TagsParser::default();
+    ///
+    /// let (remain, tags) = parser.debug_parse_optional(":nick PRIVMSG #channel :Hello").unwrap();
+    /// assert_eq!(remain, ":nick PRIVMSG #channel :Hello");
+    /// assert!(tags.0.is_empty());
+    ///
+    /// let (remain, tags) = parser.debug_parse_optional("@id=1 :nick PRIVMSG #channel").unwrap();
+    /// assert_eq!(remain, ":nick PRIVMSG #channel");
+    /// assert_eq!(tags.get("id"), Some("1"));
+    ///
+    /// let Err(nom::Err::Error(err)) = parser.debug_parse_optional("@-id=1 :nick PRIVMSG") else {
+    ///     panic!("expected an error");
+    /// };
+    /// assert_eq!(err.error, ErrorKind::InvalidKeyStart);
+    /// ```
+    pub fn debug_parse_optional<'a>(
+        &self,
+        input: &'a str,
+    ) -> IResult<&'a str, IRCv3Tags<'a>, IRCv3TagsError<&'a str>> {
+        if input.starts_with('@') {
+            self.debug_parse(input)
+        } else {
+            Ok((input, IRCv3Tags(Vec::new())))
+        }
     }
 
     /// Parse with detailed error messages
@@ -51,27 +381,224 @@ where
         &self,
         input: &'a str,
     ) -> IResult<&'a str, IRCv3Tags<'a>, IRCv3TagsError<&'a str>> {
-        if input.is_empty() || !input.starts_with('@') {
+        self.debug_parse_with_warnings(input)
+            .map(|(remain, (tags, _))| (remain, tags))
+    }
+
+    /// Like [`debug_parse`](Self::debug_parse), but also returns the tolerated problems
+    /// as [`Severity::Warning`] diagnostics, see [`allow_empty_tags`](Self::allow_empty_tags).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::{tags::IRCv3TagsParser, Severity};
+    ///
+    /// let input = "@a=b;;c; :nick PRIVMSG #channel :Hello";
+    /// let parser = IRCv3TagsParser::default().allow_empty_tags();
+    /// let (_, (tags, warnings)) = parser.debug_parse_with_warnings(input).unwrap();
+    ///
+    /// assert_eq!(tags.get("c"), Some(""));
+    /// assert_eq!(warnings.len(), 2);
+    /// assert_eq!(warnings[0].severity, Severity::Warning);
+    /// assert_eq!(warnings[0].span, 5..5);
+    /// assert_eq!(warnings[1].span, 8..8);
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn debug_parse_with_warnings<'a>(
+        &self,
+        input: &'a str,
+    ) -> IResult<&'a str, (IRCv3Tags<'a>, Vec<Diagnostic<'a>>), IRCv3TagsError<&'a str>> {
+        let tags_input = tags_start(input)?;
+        self.check_limits(input, &tags_input[..self.section_end(tags_input)])?;
+
+        let mut warnings = Vec::new();
+        let (remain, tags) = self.tags(tags_input, &mut warnings)?;
+        let remain = self.tags_end(remain)?;
+
+        self.check_count(tags_input, &tags)?;
+        let tags = self.apply_duplicate_policy(input, tags)?;
+
+        Ok((remain, (IRCv3Tags(tags), warnings)))
+    }
+
+    /// Parses the tags, skipping invalid tags instead of failing.
+    ///
+    /// Each invalid `key=value` segment is skipped up to the next ';' and reported as a
+    /// [`Diagnostic`] with the span of the segment, the valid tags are kept.
+    /// Empty segments tolerated by [`allow_empty_tags`](Self::allow_empty_tags) are
+    /// reported as [`Severity::Warning`].
+    /// A missing '@' or space after the tags, a CR, LF or NUL inside the tags section,
+    /// a tags section without any tag, the size limits and [`DuplicateKeyPolicy::Reject`]
+    /// still fail the whole parse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::{tags::IRCv3TagsParser, ErrorKind};
+    ///
+    /// let input = "@id=1;-bad=x;+typing=active :nick PRIVMSG #channel :Hello";
+    /// let (remain, (tags, diagnostics)) = IRCv3TagsParser::default().recover(input).unwrap();
+    ///
+    /// assert_eq!(remain, ":nick PRIVMSG #channel :Hello");
+    /// assert_eq!(tags.get("id"), Some("1"));
+    /// assert_eq!(tags.get("+typing"), Some("active"));
+    ///
+    /// assert_eq!(diagnostics.len(), 1);
+    /// assert_eq!(&input[diagnostics[0].span.clone()], "-bad=x");
+    /// assert_eq!(diagnostics[0].error.error, ErrorKind::InvalidKeyStart);
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn recover<'a>(
+        &self,
+        input: &'a str,
+    ) -> IResult<&'a str, (IRCv3Tags<'a>, Vec<Diagnostic<'a>>), IRCv3TagsError<&'a str>> {
+        let tags_input = tags_start(input)?;
+        let end = self.section_end(tags_input);
+        if tags_input[end..].starts_with('\0') {
             return Err(nom::Err::Error(IRCv3TagsError::new(
-                input,
-                nom::error::ErrorKind::Char,
-                ErrorKind::TagErrorStartWithLetter,
-                "tag must start with an '@'",
+                &tags_input[end..],
+                ErrorKind::ForbiddenValueChar,
+                "tag value must not contain NUL, CR or LF",
             )));
         }
+        let remain = self.tags_end(&tags_input[end..])?;
+
+        self.check_limits(input, &tags_input[..end])?;
+        if tags_input[..end].split(';').all(str::is_empty) {
+            return Err(nom::Err::Error(no_tags_error(tags_input)));
+        }
+
+        let mut tags = Vec::new();
+        let mut diagnostics = Vec::new();
+        let mut start = 0;
+
+        for segment in tags_input[..end].split(';') {
+            let span = start + 1..start + 1 + segment.len();
+
+            // A valid tag always ends at the ';' or ' ' ending its segment.
+            match self.tag(&tags_input[start..]) {
+                _ if self.allow_empty_tags && segment.is_empty() => {
+                    diagnostics.push(empty_tag_warning(&tags_input[start..], span))
+                }
+                Ok((_, tag)) => tags.push(tag),
+                Err(err) => diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    span,
+                    error: IRCv3TagsError::from(err),
+                }),
+            }
+
+            start += segment.len() + 1;
+        }
+
+        self.check_count(tags_input, &tags)?;
+        let tags = self.apply_duplicate_policy(input, tags)?;
+
+        Ok((remain, (IRCv3Tags(tags), diagnostics)))
+    }
+
+    /// Returns the length of the tags section, up to the first space, CR, LF or NUL,
+    /// so a segment never runs into the next line.
+    fn section_end(&self, tags_input: &str) -> usize {
+        tags_input
+            .find([' ', '\r', '\n', '\0'])
+            .unwrap_or(tags_input.len())
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn apply_duplicate_policy<'a>(
+        &self,
+        input: &'a str,
+        tags: Vec<(&'a str, Option<&'a str>)>,
+    ) -> Result<Vec<(&'a str, Option<&'a str>)>, nom::Err<IRCv3TagsError<&'a str>>> {
+        // Nothing can repeat in fewer than two tags, skip building the index.
+        if self.duplicate_policy == DuplicateKeyPolicy::KeepAll || tags.len() < 2 {
+            return Ok(tags);
+        }
+
+        // Position of each key in `kept`, a repeated key keeps its first position.
+        let mut index = HashMap::with_capacity(tags.len());
+        let mut kept: Vec<(&'a str, Option<&'a str>)> = Vec::with_capacity(tags.len());
+
+        for tag in tags {
+            match index.entry(TagKey::new(tag.0).normalized()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(kept.len());
+                    kept.push(tag);
+                }
+                Entry::Occupied(entry) => match self.duplicate_policy {
+                    DuplicateKeyPolicy::LastWins => kept[*entry.get()] = tag,
+                    DuplicateKeyPolicy::Reject => {
+                        let offset = tag.0.as_ptr() as usize - input.as_ptr() as usize;
+                        return Err(nom::Err::Error(IRCv3TagsError::new(
+                            &input[offset..],
+                            ErrorKind::DuplicateKey,
+                            "tag key must not be repeated",
+                        )));
+                    }
+                    DuplicateKeyPolicy::FirstWins | DuplicateKeyPolicy::KeepAll => {}
+                },
+            }
+        }
 
-        let (remain, tags) = delimited(char('@'), |i| self.try_tags(i), space1)
-            .parse(input)
-            .map_err(|err| {
-                err.map(|e| IRCv3TagsError {
-                    input: e.input,
-                    code: e.code,
-                    error: e.error,
-                    reason: e.reason,
-                })
-            })?;
+        Ok(kept)
+    }
 
-        Ok((remain, IRCv3Tags(tags)))
+    fn check_count<'a>(
+        &self,
+        input: &'a str,
+        tags: &[(&'a str, Option<&'a str>)],
+    ) -> Result<(), nom::Err<IRCv3TagsError<&'a str>>> {
+        match self.max_tags.and_then(|limit| tags.get(limit)) {
+            Some((key, _)) => {
+                let offset = key.as_ptr() as usize - input.as_ptr() as usize;
+                Err(nom::Err::Error(IRCv3TagsError::new(
+                    &input[offset..],
+                    ErrorKind::TooManyTags,
+                    "too many tags",
+                )))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the size limits on the raw tags section, before it is split into tags,
+    /// so invalid and empty segments count as well.
+    fn check_limits<'a>(
+        &self,
+        input: &'a str,
+        section: &str,
+    ) -> Result<(), nom::Err<IRCv3TagsError<&'a str>>> {
+        if let Some(limit) = self.client_tag_limit {
+            // Joined with ';', without the '@' and trailing space
+            let size = section
+                .split(';')
+                .filter(|segment| segment.starts_with('+'))
+                .map(|segment| segment.len() + 1)
+                .sum::<usize>()
+                .saturating_sub(1);
+
+            if size > limit {
+                return Err(nom::Err::Error(IRCv3TagsError::new(
+                    input,
+                    ErrorKind::TooLong(TagLimit::ClientOnly),
+                    "client-only tag data exceeds the size limit",
+                )));
+            }
+        }
+
+        if let Some(limit) = self.total_tag_limit {
+            // '@' <tags> <SPACE>
+            if section.len() + 2 > limit {
+                return Err(nom::Err::Error(IRCv3TagsError::new(
+                    input,
+                    ErrorKind::TooLong(TagLimit::Total),
+                    "tag data exceeds the size limit",
+                )));
+            }
+        }
+
+        Ok(())
     }
 
     #[allow(clippy::type_complexity)]
@@ -79,18 +606,103 @@ where
         &self,
         input: &'a str,
     ) -> IResult<&'a str, Vec<(&'a str, Option<&'a str>)>, IRCv3TagsError<&'a str>> {
-        separated_list1(char(';'), |i| self.tag(i)).parse(input)
+        self.tags(input, &mut Vec::new())
+    }
+
+    /// Parses the ';' separated tags, pushing a warning for each ignored empty segment.
+    #[allow(clippy::type_complexity)]
+    fn tags<'a>(
+        &self,
+        input: &'a str,
+        warnings: &mut Vec<Diagnostic<'a>>,
+    ) -> IResult<&'a str, Vec<(&'a str, Option<&'a str>)>, IRCv3TagsError<&'a str>> {
+        let mut tags = Vec::new();
+        let mut remain = input;
+
+        // Unlike `separated_list1`, an invalid tag after a ';' is reported
+        // instead of ending the list there.
+        loop {
+            let empty = matches!(remain.chars().next(), None | Some(';' | ' ' | '\r' | '\n'));
+            if self.allow_empty_tags && empty {
+                // Spans are relative to the line, which starts with the '@'.
+                let start = input.len() - remain.len() + 1;
+                warnings.push(empty_tag_warning(remain, start..start));
+            } else {
+                let (rest, tag) = self.tag(remain)?;
+                tags.push(tag);
+                remain = rest;
+            }
+
+            match remain.strip_prefix(';') {
+                Some(rest) => remain = rest,
+                None => break,
+            }
+        }
+
+        if tags.is_empty() {
+            return Err(nom::Err::Error(no_tags_error(input)));
+        }
+
+        Ok((remain, tags))
     }
 
     pub(crate) fn tag<'a>(
         &self,
         input: &'a str,
     ) -> IResult<&'a str, (&'a str, Option<&'a str>), IRCv3TagsError<&'a str>> {
-        (
-            |i| self.key(i),
-            opt(preceded(char('='), |c| self.escaped_value(c))),
+        let (remain, key) = self.key(input)?;
+
+        match remain.strip_prefix('=') {
+            Some(value) => {
+                let (remain, value) = self.escaped_value(value)?;
+                Ok((remain, (key.as_str(), Some(value))))
+            }
+            None => Ok((remain, (key.as_str(), None))),
+        }
+    }
+
+    /// Parses a tag key into its client prefix, vendor and key name.
+    /// [ <client_prefix> ] [ <vendor> '/' ] <key_name>
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use ircv3_tags::tags::IRCv3TagsParser;
+    ///
+    /// let parser = IRCv3TagsParser::default();
+    /// let (remain, key) = parser.try_key("+example.com/foo=bar").unwrap();
+    ///
+    /// assert_eq!(remain, "=bar");
+    /// assert!(key.is_client_only());
+    /// assert_eq!(key.vendor(), Some("example.com"));
+    /// assert_eq!(key.name(), "foo");
+    /// ```
+    pub fn try_key<'a>(
+        &self,
+        input: &'a str,
+    ) -> IResult<&'a str, TagKey<'a>, IRCv3TagsError<&'a str>> {
+        self.key(input)
+    }
+
+    fn key<'a>(&self, input: &'a str) -> IResult<&'a str, TagKey<'a>, IRCv3TagsError<&'a str>> {
+        let (remain, (client_prefix, vendor, name)) = (
+            opt(|c| self.client_prefix(c)),
+            |i| self.vendor(i),
+            |i| self.key_name(i),
         )
-            .parse(input)
+            .parse(input)?;
+
+        let raw = &input[..input.len() - remain.len()];
+
+        Ok((
+            remain,
+            TagKey::from_parts(
+                raw,
+                client_prefix.is_some(),
+                vendor.map(|v| v.as_str()),
+                name,
+            ),
+        ))
     }
 
     /// Parses a vendor part of the tag which follows the format `vendor/` where vendor
@@ -100,66 +712,70 @@ where
     /// - Only alphanumeric characters, dots '.', and hyphens '-'
     /// - Segments cannot start or end with a hyphen '-'
     /// - Must end with a forward slash '/'
-    fn key<'a>(&self, input: &'a str) -> IResult<&'a str, &'a str, IRCv3TagsError<&'a str>> {
-        recognize((
-            opt(|c| self.client_prefix(c)),
-            opt(terminated(
-                |i| {
-                    self.host_validator.try_host(i).map_err(|err| {
-                        err.map(|e| IRCv3TagsError {
-                            input: e.input,
-                            code: e.code,
-                            error: e.error,
-                            reason: e.reason,
-                        })
-                    })
-                },
-                char('/'),
-            )),
-            |i| self.key_name(i),
-        ))
-        .parse(input)
-        .map_err(|err| {
-            err.map(|e| IRCv3TagsError {
-                input: e.input,
-                code: e.code,
-                error: e.error,
-                reason: e.reason,
-            })
-        })
+    ///
+    /// A key with a '/' must have a valid vendor, the host error is kept as the
+    /// source of the returned error.
+    fn vendor<'a>(
+        &self,
+        input: &'a str,
+    ) -> IResult<&'a str, Option<Host<'a>>, IRCv3TagsError<&'a str>> {
+        let key_end = input.find(is_tag_end).unwrap_or(input.len());
+        if !input[..key_end].contains('/') {
+            return Ok((input, None));
+        }
+
+        let vendor = self
+            .host_validator
+            .try_host(input)
+            .and_then(|(remain, host)| match remain.strip_prefix('/') {
+                Some(remain) => Ok((remain, Some(host))),
+                None => Err(nom::Err::Error(HostError::new(
+                    remain,
+                    ErrorKind::Host,
+                    "host contains an invalid character",
+                ))),
+            });
+
+        match vendor {
+            Err(_) if self.tag_name_validator.is_valid_char('/') => Ok((input, None)),
+            vendor => vendor.map_err(|err| err.map(IRCv3TagsError::from)),
+        }
     }
 
     fn key_name<'a>(&self, input: &'a str) -> IResult<&'a str, &'a str, IRCv3TagsError<&'a str>> {
-        if input.is_empty() {
+        let Some(first_char) = input.chars().next().filter(|c| !is_tag_end(*c)) else {
             return Err(nom::Err::Error(IRCv3TagsError::new(
                 input,
-                nom::error::ErrorKind::Char,
-                ErrorKind::Empty,
-                "tag key does not allow empty",
+                ErrorKind::EmptyKey,
+                "tag key must not be empty",
             )));
-        }
+        };
 
-        let first_char = input.chars().next().unwrap();
         if !self.tag_name_validator.is_valid_start_char(first_char) {
-            return Err(nom::Err::Error(IRCv3TagsError {
+            return Err(nom::Err::Error(IRCv3TagsError::new(
                 input,
-                code: nom::error::ErrorKind::Char,
-                error: ErrorKind::TagErrorStartWithLetter,
-                reason: "tag key must start with an allowed character",
-            }));
+                ErrorKind::InvalidKeyStart,
+                "tag key must start with an allowed character",
+            )));
         }
 
         let (remain, key_name_str) = self.tag_name_validator.while_valid(input, first_char);
-        if remain.is_empty() {
-            return Err(nom::Err::Error(IRCv3TagsError {
-                input,
-                code: nom::error::ErrorKind::Char,
-                error: ErrorKind::Empty,
-                reason: "tag key must not be empty",
-            }));
+        match remain.chars().next() {
+            Some('=' | ';' | ' ') => Ok((remain, key_name_str)),
+            _ if self.separator == TagsSeparator::SpacesOrLineEnd && is_line_end(remain) => {
+                Ok((remain, key_name_str))
+            }
+            None | Some('\r' | '\n') => Err(nom::Err::Error(IRCv3TagsError::new(
+                remain,
+                ErrorKind::MissingSpace,
+                "tags must be followed by a space",
+            ))),
+            Some(_) => Err(nom::Err::Error(IRCv3TagsError::new(
+                remain,
+                ErrorKind::InvalidKeyChar,
+                "tag key contains an invalid character",
+            ))),
         }
-
-        Ok((remain, key_name_str))
     }
 
     /// Parses an escaped value which is a sequence of zero or more UTF-8 characters
@@ -168,7 +784,70 @@ where
         &self,
         input: &'a str,
     ) -> IResult<&'a str, &'a str, IRCv3TagsError<&'a str>> {
-        take_till(|c| c == '\0' || c == '\r' || c == '\n' || c == ';' || c == ' ').parse(input)
+        let (remain, value) =
+            take_till(|c| c == '\0' || c == '\r' || c == '\n' || c == ';' || c == ' ')
+                .parse(input)?;
+
+        if remain.starts_with(['\0', '\r', '\n']) {
+            let at_line_end = is_line_end(remain);
+            if at_line_end && self.separator == TagsSeparator::SpacesOrLineEnd {
+                return Ok((remain, value));
+            }
+            return Err(nom::Err::Error(if at_line_end {
+                IRCv3TagsError::new(
+                    remain,
+                    ErrorKind::MissingSpace,
+                    "tags must be followed by a space",
+                )
+            } else {
+                IRCv3TagsError::new(
+                    remain,
+                    ErrorKind::ForbiddenValueChar,
+                    "tag value must not contain NUL, CR or LF",
+                )
+            }));
+        }
+
+        if self.reject_invalid_escapes {
+            if let Some(offset) = invalid_escape(value) {
+                return Err(nom::Err::Error(IRCv3TagsError::new(
+                    &input[offset..],
+                    ErrorKind::InvalidEscape,
+                    "tag value contains an invalid escape",
+                )));
+            }
+        }
+
+        Ok((remain, value))
+    }
+
+    /// Skips the separator after the tags, see [`TagsSeparator`].
+    fn tags_end<'a>(&self, input: &'a str) -> Result<&'a str, nom::Err<IRCv3TagsError<&'a str>>> {
+        if self.separator == TagsSeparator::SpacesOrLineEnd && is_line_end(input) {
+            return Ok(&input[input.len()..]);
+        }
+
+        let Some(remain) = input.strip_prefix(' ') else {
+            return Err(nom::Err::Error(IRCv3TagsError::new(
+                input,
+                ErrorKind::MissingSpace,
+                "tags must be followed by a space",
+            )));
+        };
+
+        match self.separator {
+            TagsSeparator::SingleSpace if remain.starts_with(' ') => {
+                Err(nom::Err::Error(IRCv3TagsError::new(
+                    remain,
+                    ErrorKind::ExtraSpace,
+                    "tags must be followed by a single space",
+                )))
+            }
+            TagsSeparator::SingleSpace => Ok(remain),
+            TagsSeparator::Spaces | TagsSeparator::SpacesOrLineEnd => {
+                Ok(remain.trim_start_matches(' '))
+            }
+        }
     }
 
     fn client_prefix<'a>(&self, input: &'a str) -> IResult<&'a str, char, IRCv3TagsError<&'a str>> {
@@ -176,9 +855,132 @@ where
     }
 }
 
+/// The size limit that was exceeded, see [`ErrorKind::TooLong`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagLimit {
+    /// Limit on client-only tags, set with [`IRCv3TagsParser::client_tag_limit`]
+    ClientOnly,
+    /// Limit on all tags, set with [`IRCv3TagsParser::total_tag_limit`]
+    Total,
+}
+
+/// How a tag key that appears more than once is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    /// Keep only the last value, as the specification requires,
+    /// at the position where the key first appeared.
+    #[default]
+    LastWins,
+    /// Keep only the first value.
+    FirstWins,
+    /// Fail with [`ErrorKind::DuplicateKey`].
+    Reject,
+    /// Keep every value, see [`IRCv3Tags::get_all`](crate::IRCv3Tags::get_all).
+    KeepAll,
+}
+
+/// What separates the tags from the rest of the message.
+///
+/// Only spaces are separators, a tab is part of the tag value before it
+/// as the specification allows tabs in values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TagsSeparator {
+    /// Exactly one space, as the specification requires.
+    /// More spaces fail with [`ErrorKind::ExtraSpace`].
+    SingleSpace,
+    /// One or more spaces, all of them are consumed. Used by default.
+    #[default]
+    Spaces,
+    /// Like [`Spaces`](Self::Spaces), but the tags may also end the input,
+    /// optionally followed by a line ending which is consumed, e.g. `@a=b\r\n`.
+    /// Meant for lines that only carry tags.
+    SpacesOrLineEnd,
+}
+
+/// Strips the leading '@' of the tags.
+fn tags_start(input: &str) -> Result<&str, nom::Err<IRCv3TagsError<&str>>> {
+    input.strip_prefix('@').ok_or_else(|| {
+        nom::Err::Error(IRCv3TagsError::new(
+            input,
+            ErrorKind::MissingAt,
+            "tag must start with an '@'",
+        ))
+    })
+}
+
+/// Whether the input is only a line ending, or empty.
+fn is_line_end(input: &str) -> bool {
+    matches!(input, "" | "\r\n" | "\n" | "\r")
+}
+
+fn empty_tag_warning(input: &str, span: Range<usize>) -> Diagnostic<'_> {
+    Diagnostic {
+        severity: Severity::Warning,
+        span,
+        error: IRCv3TagsError::new(input, ErrorKind::EmptyKey, "empty tag ignored"),
+    }
+}
+
+/// Error for a tags section with only empty segments, e.g. `@ ` or `@; `.
+fn no_tags_error(input: &str) -> IRCv3TagsError<&str> {
+    IRCv3TagsError::new(input, ErrorKind::EmptyKey, "tags must not be empty")
+}
+
+/// Characters that end a tag key or the whole tags section.
+fn is_tag_end(c: char) -> bool {
+    matches!(c, '=' | ';' | ' ' | '\r' | '\n' | '\0')
+}
+
+/// Returns the byte offset of the first invalid escape in a raw tag value.
+fn invalid_escape(value: &str) -> Option<usize> {
+    let mut chars = value.char_indices();
+
+    while let Some((i, c)) = chars.next() {
+        if c == '\\' && !matches!(chars.next(), Some((_, ':' | 's' | '\\' | 'r' | 'n'))) {
+            return Some(i);
+        }
+    }
+
+    None
+}
+
+/// Key names of ASCII letters, digits and hyphens starting with a letter, used by default.
 #[derive(Debug, Clone, Default)]
 pub struct StandardTagValidator;
 impl CharValidator for StandardTagValidator {
+    fn is_valid_char(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '-'
+    }
+
+    fn is_valid_start_char(&self, c: char) -> bool {
+        c.is_ascii_alphabetic()
+    }
+}
+
+/// Key names exactly as the message-tags grammar defines them:
+/// a non-empty sequence of ASCII letters, digits and hyphens.
+///
+/// Unlike [`StandardTagValidator`] a key name may start with a digit or a hyphen.
+/// Used by [`IRCv3TagsParser::strict`].
+#[derive(Debug, Clone, Default)]
+pub struct StrictTagValidator;
+impl CharValidator for StrictTagValidator {
+    fn is_valid_char(&self, c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '-'
+    }
+
+    fn is_valid_start_char(&self, c: char) -> bool {
+        self.is_valid_char(c)
+    }
+}
+
+/// Key names of any Unicode letters and digits plus hyphens, starting with an ASCII letter.
+///
+/// This does not follow the message-tags grammar, use it only for peers that send such keys.
+/// Used by [`IRCv3TagsParser::lenient`].
+#[derive(Debug, Clone, Default)]
+pub struct LenientTagValidator;
+impl CharValidator for LenientTagValidator {
     fn is_valid_char(&self, c: char) -> bool {
         c.is_alphanumeric() || c == '-'
     }
@@ -188,6 +990,12 @@ impl CharValidator for StandardTagValidator {
     }
 }
 
+/// Key names of ASCII letters, digits and hyphens starting with an ASCII letter, like
+/// [`StandardTagValidator`], plus the characters allowed with
+/// [`allow_chars`](Self::allow_chars) and [`allow_start_chars`](Self::allow_start_chars).
+///
+/// Other non-ASCII characters are rejected unless allowed explicitly, see
+/// [`LenientTagValidator`] for Unicode key names.
 #[derive(Debug, Clone, Default)]
 pub struct CustomTagNameValidator {
     extra_chars: Vec<char>,
@@ -215,7 +1023,7 @@ impl CustomTagNameValidator {
 
 impl CharValidator for CustomTagNameValidator {
     fn is_valid_char(&self, c: char) -> bool {
-        c.is_alphanumeric() || c == '-' || self.extra_chars.contains(&c)
+        c.is_ascii_alphanumeric() || c == '-' || self.extra_chars.contains(&c)
     }
 
     fn is_valid_start_char(&self, c: char) -> bool {
@@ -0,0 +1,213 @@
+use crate::{
+    escape_value,
+    serialize::{self, validate_key, validate_value},
+    tag_key, unescaped_to_escaped_strict, IRCv3Tags, IRCv3TagsError, TagKey, TagValue,
+};
+
+/// An owned, mutable list of IRCv3 message tags.
+///
+/// `IRCv3TagsBuf` is to [`IRCv3Tags`] what `String` is to `&str`: parsed tags can be
+/// converted into it, modified and written back to the wire. Order is preserved, new
+/// tags are appended at the end and replaced tags keep their position.
+///
+/// Values are stored in their wire (escaped) form, so tags copied from a parsed
+/// message are relayed unchanged. Values passed to [`insert`](Self::insert) and
+/// [`set`](Self::set) are escaped on the way in and must not contain NUL, and their
+/// keys must be accepted by [`crate::parse`], so the written tags always parse back.
+///
+/// # Examples
+///
+/// ```
+/// use ircv3_tags::IRCv3TagsBuf;
+///
+/// let input = "@msgid=abc;+typing=active;draft/foo=bar :nick!user@host PRIVMSG #channel :Hello";
+/// let (remain, tags) = ircv3_tags::parse(input);
+///
+/// let mut tags = IRCv3TagsBuf::from(tags);
+/// tags.set("time", "2025-05-04T12:34:56.000Z").unwrap();
+/// tags.set("account", "some user").unwrap();
+/// assert!(tags.set("bad key", "x").is_err());
+/// tags.remove("+typing");
+///
+/// assert_eq!(
+///     tags.to_wire(),
+///     "@msgid=abc;draft/foo=bar;time=2025-05-04T12:34:56.000Z;account=some\\suser "
+/// );
+/// assert_eq!(tags.get_escaped("account"), Some("some user".to_string()));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IRCv3TagsBuf(Vec<(String, Option<String>)>);
+
+impl IRCv3TagsBuf {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Gets the raw value for a key without unescaping.
+    ///
+    /// * `None` if the key doesn't exist
+    /// * `Some("")` if the key exists with an empty value
+    /// * `Some(value)` if the key exists with a value
+    pub fn get<K: AsRef<str>>(&self, key: K) -> Option<&str> {
+        self.position(key.as_ref())
+            .map(|i| self.0[i].1.as_deref().unwrap_or(""))
+    }
+
+    /// Gets the escaped value for a key, see [`IRCv3Tags::get_escaped`].
+    pub fn get_escaped<K: AsRef<str>>(&self, key: K) -> Option<String> {
+        self.get(key).map(unescaped_to_escaped_strict)
+    }
+
+    /// Gets a lazily unescaped view of the value for a key.
+    pub fn get_value<K: AsRef<str>>(&self, key: K) -> Option<TagValue<'_>> {
+        self.get(key).map(TagValue::new)
+    }
+
+    /// Inserts a tag, escaping its value.
+    ///
+    /// If the key already exists its value is replaced in place and the previous raw
+    /// value is returned, otherwise the tag is appended and `None` is returned.
+    /// Pass `None` as the value for a tag without a value.
+    ///
+    /// The key must be one [`crate::parse`] accepts, see
+    /// [`IRCv3TagsParser::default`](crate::tags::IRCv3TagsParser::default), with an
+    /// internationalized vendor checked as A-labels. The value must not contain NUL, the
+    /// only character escaping cannot represent. Otherwise nothing is inserted and the
+    /// error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ircv3_tags::{ErrorKind, IRCv3TagsBuf};
+    ///
+    /// let mut tags = IRCv3TagsBuf::new();
+    /// assert_eq!(tags.insert("+example.com/flag", None), Ok(None));
+    ///
+    /// let err = tags.insert("bad key;x", Some("v")).unwrap_err();
+    /// assert_eq!(err.error, ErrorKind::InvalidKeyChar);
+    /// assert_eq!(tags.insert("", Some("v")).unwrap_err().error, ErrorKind::EmptyKey);
+    /// assert_eq!(tags.insert("1abc", None).unwrap_err().error, ErrorKind::InvalidKeyStart);
+    ///
+    /// let err = tags.insert("a", Some("x\0y")).unwrap_err();
+    /// assert_eq!(err.error, ErrorKind::ForbiddenValueChar);
+    /// assert_eq!(tags.to_wire(), "@+example.com/flag ");
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn insert<K: Into<String>>(
+        &mut self,
+        key: K,
+        value: Option<&str>,
+    ) -> Result<Option<Option<String>>, IRCv3TagsError<String>> {
+        let key = key.into();
+        validate_key(&key)?;
+        if let Some(value) = value {
+            validate_value(value)?;
+        }
+
+        let value = value.map(|v| escape_value(v).into_owned());
+        Ok(self.insert_raw(key, value))
+    }
+
+    /// Sets a tag with a value, escaping it. Same as `insert(key, Some(value))`.
+    #[allow(clippy::type_complexity)]
+    pub fn set<K: Into<String>>(
+        &mut self,
+        key: K,
+        value: &str,
+    ) -> Result<Option<Option<String>>, IRCv3TagsError<String>> {
+        self.insert(key, Some(value))
+    }
+
+    /// Removes every tag with the given key, returning `true` if any was removed.
+    pub fn remove<K: AsRef<str>>(&mut self, key: K) -> bool {
+        let key = TagKey::new(key.as_ref()).normalized();
+        let len = self.0.len();
+        self.0.retain(|(k, _)| !tag_key::matches_key(k, &key));
+        self.0.len() != len
+    }
+
+    /// Keeps only the tags for which the predicate returns `true`.
+    ///
+    /// The predicate receives the key and the raw value.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&str, Option<&str>) -> bool,
+    {
+        self.0.retain(|(k, v)| f(k, v.as_deref()));
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_deref()))
+    }
+
+    /// Borrows the tags as an [`IRCv3Tags`].
+    pub fn as_tags(&self) -> IRCv3Tags<'_> {
+        IRCv3Tags(self.iter().collect())
+    }
+
+    /// Serializes the tags into the tags section of an IRC message.
+    /// ['@' <tags> <SPACE>]
+    pub fn to_wire(&self) -> String {
+        serialize::serialize_raw(self.iter())
+    }
+
+    fn insert_raw<K: Into<String>>(
+        &mut self,
+        key: K,
+        value: Option<String>,
+    ) -> Option<Option<String>> {
+        let key = key.into();
+        match self.position(&key) {
+            Some(i) => Some(std::mem::replace(&mut self.0[i].1, value)),
+            None => {
+                self.0.push((key, value));
+                None
+            }
+        }
+    }
+
+    fn position(&self, key: &str) -> Option<usize> {
+        let key = TagKey::new(key).normalized();
+        self.0
+            .iter()
+            .rposition(|(k, _)| tag_key::matches_key(k, &key))
+    }
+}
+
+impl From<IRCv3Tags<'_>> for IRCv3TagsBuf {
+    fn from(tags: IRCv3Tags<'_>) -> Self {
+        Self::from(&tags)
+    }
+}
+
+impl From<&IRCv3Tags<'_>> for IRCv3TagsBuf {
+    fn from(tags: &IRCv3Tags<'_>) -> Self {
+        Self(
+            tags.0
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.map(str::to_string)))
+                .collect(),
+        )
+    }
+}
+
+impl IntoIterator for IRCv3TagsBuf {
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+    type Item = (String, Option<String>);
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
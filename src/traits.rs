@@ -26,3 +26,20 @@ pub trait CharValidator {
         (&input[position..], &input[..position])
     }
 }
+
+/// Host rules on top of the [`CharValidator`] rules for labels, used by
+/// [`RFC952HostParser`](crate::host::RFC952HostParser).
+pub trait HostValidator: CharValidator {
+    /// Maximum length in bytes of a host label.
+    fn max_label_len(&self) -> Option<usize> {
+        None
+    }
+    /// Maximum length in bytes of a whole host, not counting a trailing dot.
+    fn max_host_len(&self) -> Option<usize> {
+        None
+    }
+    /// Whether a host may end with a '.'.
+    fn allow_trailing_dot(&self) -> bool {
+        false
+    }
+}
@@ -1,3 +1,5 @@
+use std::str::Chars;
+
 /// Unescapes an IRCv3 tag value according to the specification.
 ///
 /// The following sequences are unescaped:
@@ -17,29 +19,108 @@
 /// assert_eq!(unescaped_to_escaped("back\\\\slash"), "back\\slash");
 /// ```
 pub fn unescaped_to_escaped(value: &str) -> String {
+    unescape_with_mode(value, UnescapeMode::Lenient)
+}
+
+/// Unescapes an IRCv3 tag value following the specification strictly.
+///
+/// Handles the same sequences as [`unescaped_to_escaped`], but:
+/// - an invalid escape drops the backslash (`\b` → `b`)
+/// - a trailing backslash is removed
+///
+/// # Examples
+///
+/// ```
+/// use ircv3_tags::unescaped_to_escaped_strict;
+///
+/// assert_eq!(unescaped_to_escaped_strict("hello\\sworld"), "hello world");
+/// assert_eq!(unescaped_to_escaped_strict("invalid\\bescape"), "invalidbescape");
+/// assert_eq!(unescaped_to_escaped_strict("trailing\\"), "trailing");
+/// ```
+pub fn unescaped_to_escaped_strict(value: &str) -> String {
+    unescape_with_mode(value, UnescapeMode::Strict)
+}
+
+pub(crate) fn unescape_with_mode(value: &str, mode: UnescapeMode) -> String {
     let mut result = String::with_capacity(value.len());
-    let mut chars = value.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        if c == '\\' {
-            match chars.next() {
-                Some(':') => result.push(';'),
-                Some('s') => result.push(' '),
-                Some('\\') => result.push('\\'),
-                Some('r') => result.push('\r'),
-                Some('n') => result.push('\n'),
-                Some(other) => {
-                    result.push('\\');
-                    result.push(other);
-                }
-                None => {
-                    result.push('\\');
+    result.extend(UnescapedChars::with_mode(value, mode));
+    result
+}
+
+/// How invalid escape sequences are handled when unescaping a tag value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UnescapeMode {
+    /// Drops the backslash of an invalid escape and a trailing backslash, as the specification requires.
+    #[default]
+    Strict,
+    /// Keeps invalid escapes and a trailing backslash unchanged.
+    Lenient,
+}
+
+/// An iterator over the unescaped characters of an IRCv3 tag value.
+///
+/// `new` yields the same characters as [`unescaped_to_escaped`] without allocating,
+/// `with_mode` allows choosing the [`UnescapeMode`].
+///
+/// # Examples
+///
+/// ```
+/// use ircv3_tags::UnescapedChars;
+///
+/// let chars: Vec<char> = UnescapedChars::new("a\\sb\\:").collect();
+/// assert_eq!(chars, ['a', ' ', 'b', ';']);
+/// ```
+#[derive(Debug, Clone)]
+pub struct UnescapedChars<'a> {
+    chars: Chars<'a>,
+    pending: Option<char>,
+    mode: UnescapeMode,
+}
+
+impl<'a> UnescapedChars<'a> {
+    pub fn new(value: &'a str) -> Self {
+        Self::with_mode(value, UnescapeMode::Lenient)
+    }
+
+    pub fn with_mode(value: &'a str, mode: UnescapeMode) -> Self {
+        Self {
+            chars: value.chars(),
+            pending: None,
+            mode,
+        }
+    }
+}
+
+impl Iterator for UnescapedChars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if let Some(c) = self.pending.take() {
+            return Some(c);
+        }
+
+        match self.chars.next()? {
+            '\\' => match (self.chars.next(), self.mode) {
+                (Some(':'), _) => Some(';'),
+                (Some('s'), _) => Some(' '),
+                (Some('\\'), _) => Some('\\'),
+                (Some('r'), _) => Some('\r'),
+                (Some('n'), _) => Some('\n'),
+                (Some(other), UnescapeMode::Strict) => Some(other),
+                (Some(other), UnescapeMode::Lenient) => {
+                    self.pending = Some(other);
+                    Some('\\')
                 }
-            }
-        } else {
-            result.push(c);
+                (None, UnescapeMode::Strict) => None,
+                (None, UnescapeMode::Lenient) => Some('\\'),
+            },
+            c => Some(c),
         }
     }
 
-    result
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (_, upper) = self.chars.size_hint();
+        let pending = usize::from(self.pending.is_some());
+        (pending, upper.map(|upper| upper + pending))
+    }
 }
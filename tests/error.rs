@@ -0,0 +1,318 @@
+#![allow(deprecated)]
+
+use std::error::Error;
+
+use ircv3_tags::{
+    host::{try_host, HostError},
+    message::{try_message, MessageError},
+    source::{try_source, SourceError},
+    tags::{CustomTagNameValidator, IRCv3TagsParser},
+    ErrorKind, IRCv3TagsError, Location,
+};
+
+#[test]
+fn display() {
+    let err = HostError::from(try_host("a--b.com rest").unwrap_err());
+    assert_eq!(
+        err.to_string(),
+        "label contains an invalid character at \"a--b.com\""
+    );
+
+    let err = IRCv3TagsError::from(ircv3_tags::debug_parse("foo bar").unwrap_err());
+    assert_eq!(err.to_string(), "tag must start with an '@' at \"foo\"");
+
+    let err = IRCv3TagsError::from(ircv3_tags::debug_parse("").unwrap_err());
+    assert_eq!(
+        err.to_string(),
+        "tag must start with an '@' at end of input"
+    );
+
+    let err = SourceError::from(try_source("nick!@host").unwrap_err());
+    assert_eq!(err.to_string(), "user must not be empty at \"@host\"");
+
+    let long = format!("{} PRIVMSG", "a".repeat(40));
+    let err = IRCv3TagsError::from(ircv3_tags::debug_parse(&long).unwrap_err());
+    assert_eq!(
+        err.to_string(),
+        format!("tag must start with an '@' at \"{}\"...", "a".repeat(32))
+    );
+}
+
+#[test]
+fn source_chain() {
+    let input = "@example-.com/foo=bar PRIVMSG #channel";
+    let err = IRCv3TagsError::from(ircv3_tags::debug_parse(input).unwrap_err());
+    assert_eq!(err.error, ErrorKind::InvalidVendor);
+    assert_eq!(err.reason, "tag key has an invalid vendor");
+    assert_eq!(
+        err.host_error(),
+        Some(&HostError::new(
+            "example-.com/foo=bar PRIVMSG #channel",
+            ErrorKind::Host,
+            "label contains an invalid character",
+        ))
+    );
+
+    let err = err.into_owned();
+    let source = err.source().expect("host error as source");
+    assert_eq!(
+        source.to_string(),
+        "label contains an invalid character at \"example-.com/foo=bar\""
+    );
+    assert!(source.source().is_none());
+
+    let err =
+        IRCv3TagsError::from(ircv3_tags::debug_parse("@exa_mple.com/foo PRIVMSG").unwrap_err());
+    assert_eq!(err.reason, "tag key has an invalid vendor");
+    assert_eq!(
+        err.host_error().map(|e| e.reason),
+        Some("host contains an invalid character")
+    );
+
+    let err = IRCv3TagsError::from(ircv3_tags::debug_parse("@=foo PRIVMSG").unwrap_err());
+    assert!(err.host_error().is_none());
+    assert!(err.into_owned().source().is_none());
+
+    let err = SourceError::from(try_source("nick!user@host-.com").unwrap_err());
+    assert_eq!(
+        err.host_error(),
+        Some(&HostError::new(
+            "host-.com",
+            ErrorKind::Host,
+            "label contains an invalid character",
+        ))
+    );
+    let source = err.into_owned();
+    assert!(source.source().is_some());
+
+    let line = "@a=1 :irc..example.com PRIVMSG #channel";
+    let err = MessageError::from(try_message(line).unwrap_err()).into_owned();
+    assert_eq!(err.reason, "label must start with an allowed character");
+    assert_eq!(
+        err.source().map(|e| e.to_string()),
+        Some("label must start with an allowed character at \".example.com\"".to_string())
+    );
+
+    let err = SourceError::from(try_source("nick!@host").unwrap_err());
+    assert!(err.host_error().is_none());
+    assert!(err.into_owned().source().is_none());
+}
+
+#[test]
+fn conversions() {
+    let host = HostError::new(
+        "bad_host",
+        ErrorKind::Host,
+        "label contains an invalid character",
+    );
+    let tags = IRCv3TagsError::from(host);
+    assert_eq!(tags.input, "bad_host");
+    assert_eq!(tags.error, ErrorKind::InvalidVendor);
+    assert!(tags.host_error().is_some());
+
+    let message = MessageError::from(tags);
+    assert_eq!(message.reason, "tag key has an invalid vendor");
+    assert!(message.host_error().is_some());
+    assert!(message.into_owned().source().is_some());
+
+    let host = HostError::new(
+        "bad_host",
+        ErrorKind::Host,
+        "label contains an invalid character",
+    );
+    let source = SourceError::from(host);
+    assert!(source.host_error().is_some());
+    let message = MessageError::from(source);
+    assert_eq!(
+        message,
+        MessageError::new(
+            "bad_host",
+            ErrorKind::Host,
+            "label contains an invalid character"
+        )
+    );
+    assert_eq!(
+        message.host_error().map(|e| e.reason),
+        Some("label contains an invalid character")
+    );
+
+    let err = MessageError::from(try_message("@a.b/=x PRIVMSG").unwrap_err());
+    assert_eq!(err.error, ErrorKind::EmptyKey);
+}
+
+#[test]
+fn anyhow_style_question_mark() {
+    fn parse(input: &str) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let parser = IRCv3TagsParser::default();
+        let (_, tags) = parser
+            .debug_parse(input)
+            .map_err(|err| IRCv3TagsError::from(err.map(IRCv3TagsError::into_owned)))?;
+        Ok(tags.0.len())
+    }
+
+    assert_eq!(parse("@a=1;b PRIVMSG").unwrap(), 2);
+
+    let err = parse("@-b PRIVMSG").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "tag key must start with an allowed character at \"-b\""
+    );
+}
+
+#[test]
+fn location() {
+    let line = "@a=1;+b=2;-c=3 PRIVMSG #channel";
+    let err = IRCv3TagsError::from(ircv3_tags::debug_parse(line).unwrap_err());
+    assert_eq!(
+        err.location(line),
+        Some(Location {
+            offset: 10,
+            char_offset: 10,
+            span: 10..14,
+        })
+    );
+    assert_eq!(&line[err.location(line).unwrap().span], "-c=3");
+
+    let line = "@é=ü;ü PRIVMSG";
+    let parser = IRCv3TagsParser::new(CustomTagNameValidator::new().allow_start_chars(&['é']));
+    let err = IRCv3TagsError::from(parser.debug_parse(line).unwrap_err());
+    let location = err.location(line).unwrap();
+    assert_eq!(location.offset, 7);
+    assert_eq!(location.char_offset, 5);
+    assert_eq!(&line[location.span.clone()], "ü");
+
+    let owned = err.into_owned();
+    assert_eq!(owned.location(line), Some(location));
+    assert_eq!(owned.location("something else"), None);
+
+    let line = "nick!user@bad_host";
+    let err = SourceError::from(try_source(line).unwrap_err());
+    assert_eq!(err.location(line).map(|l| l.offset), Some(13));
+
+    let line = "@a=1 :nick!user@ PRIVMSG #channel";
+    let err = MessageError::from(try_message(line).unwrap_err());
+    let location = err.location(line).unwrap();
+    assert_eq!(location.offset, 16);
+    assert_eq!(location.span, 16..16);
+}
+
+#[test]
+fn location_spans() {
+    fn location(
+        parser: &IRCv3TagsParser<impl ircv3_tags::CharValidator, impl ircv3_tags::HostValidator>,
+        line: &str,
+    ) -> (ErrorKind, usize, String) {
+        let err = IRCv3TagsError::from(parser.debug_parse(line).unwrap_err());
+        let location = err.location(line).unwrap();
+        (err.error, location.offset, line[location.span].to_string())
+    }
+
+    // Key and value errors span the whole tag
+    let parser = IRCv3TagsParser::default();
+    let cases = [
+        ("@a$b=1 X", ErrorKind::InvalidKeyChar, 2, "a$b=1"),
+        ("@x=1;a$b=1;c X", ErrorKind::InvalidKeyChar, 6, "a$b=1"),
+        ("@a=1\0b X", ErrorKind::ForbiddenValueChar, 4, "a=1"),
+        ("@a;;b X", ErrorKind::EmptyKey, 3, ""),
+    ];
+    for (line, error, offset, span) in cases {
+        assert_eq!(
+            location(&parser, line),
+            (error, offset, span.to_string()),
+            "{:?}",
+            line
+        );
+    }
+
+    let parser = IRCv3TagsParser::default().reject_invalid_escapes();
+    assert_eq!(
+        location(&parser, "@a=1;b=x\\y X"),
+        (ErrorKind::InvalidEscape, 8, "b=x\\y".to_string())
+    );
+
+    // Limit errors span the whole tags section
+    let parser = IRCv3TagsParser::default().total_tag_limit(5);
+    assert_eq!(location(&parser, "@a=1;b=2 X").2, "@a=1;b=2");
+    let parser = IRCv3TagsParser::default().max_tags(1);
+    assert_eq!(
+        location(&parser, "@a=1;b=2 X"),
+        (ErrorKind::TooManyTags, 5, "@a=1;b=2".to_string())
+    );
+
+    // Other errors span from the error
+    let parser = IRCv3TagsParser::default();
+    assert_eq!(location(&parser, "@a=1").2, "");
+
+    let line = "@a=1;b$c=2 X";
+    let err = IRCv3TagsError::from(parser.debug_parse(line).unwrap_err());
+    assert_eq!(
+        err.render(line),
+        "error: tag key contains an invalid character\n \
+         --> byte 6, char 6\n  \
+         |\n  \
+         | @a=1;b$c=2 X\n  \
+         |      ~^~~~\n"
+    );
+}
+
+#[test]
+fn render() {
+    let line = "@a=1;-b=2 PRIVMSG #channel\r\n";
+    let err = IRCv3TagsError::from(ircv3_tags::debug_parse(line).unwrap_err());
+    assert_eq!(
+        err.render(line),
+        "error: tag key must start with an allowed character\n \
+         --> byte 5, char 5\n  \
+         |\n  \
+         | @a=1;-b=2 PRIVMSG #channel\n  \
+         |      ^~~~\n"
+    );
+
+    let line = "@a= PRIVMSG";
+    let err = IRCv3TagsError::new(&line[3..], ErrorKind::Empty, "value must not be empty");
+    assert_eq!(
+        err.render(line),
+        "error: value must not be empty\n \
+         --> byte 3, char 3\n  \
+         |\n  \
+         | @a= PRIVMSG\n  \
+         |    ^\n"
+    );
+
+    let err = IRCv3TagsError::new("elsewhere", ErrorKind::Empty, "value must not be empty");
+    assert_eq!(err.render(line), "error: value must not be empty\n");
+}
+
+#[test]
+fn render_long_line() {
+    let tags = (0..200)
+        .map(|i| format!("tag{}=value", i))
+        .collect::<Vec<_>>()
+        .join(";");
+    let line = format!(
+        "@{};-bad=1;ok=1 PRIVMSG #channel :{}",
+        tags,
+        "Hello ".repeat(20)
+    );
+    assert!(line.len() > 2000);
+
+    let err = IRCv3TagsError::from(ircv3_tags::debug_parse(&line).unwrap_err());
+    let location = err.location(&line).unwrap();
+    assert_eq!(&line[location.span.clone()], "-bad=1");
+
+    let rendered = err.render(&line);
+    let lines = rendered.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(
+        lines[1],
+        format!(" --> byte {}, char {}", location.offset, location.offset)
+    );
+    assert!(lines[3].starts_with("  | ..."));
+    assert!(lines[3].ends_with("..."));
+    assert!(lines[3].contains(";-bad=1;ok=1 PRIVMSG"));
+    assert!(lines[3].len() < 120);
+
+    let caret = lines[4].find('^').unwrap();
+    assert_eq!(&lines[3][caret..caret + 6], "-bad=1");
+    assert!(lines[4].ends_with("^~~~~~"));
+}
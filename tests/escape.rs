@@ -0,0 +1,68 @@
+#![allow(deprecated)]
+
+use std::borrow::Cow;
+
+use ircv3_tags::{escape_value, escape_value_fmt, escape_value_io, unescaped_to_escaped};
+use proptest::prelude::*;
+
+#[test]
+fn test_escape_value() {
+    assert_eq!(escape_value("hello world"), "hello\\sworld");
+    assert_eq!(escape_value("semi;colon"), "semi\\:colon");
+    assert_eq!(escape_value("back\\slash"), "back\\\\slash");
+    assert_eq!(escape_value("new\nline"), "new\\nline");
+    assert_eq!(escape_value("carriage\rreturn"), "carriage\\rreturn");
+    assert_eq!(escape_value("a;b c\\d\re\nf"), r"a\:b\sc\\d\re\nf");
+    assert_eq!(escape_value("\\s"), r"\\s");
+    assert_eq!(escape_value("trailing\\"), r"trailing\\");
+    assert_eq!(escape_value("名前 値"), r"名前\s値");
+    assert_eq!(escape_value(""), "");
+}
+
+#[test]
+fn test_escape_borrowed() {
+    assert!(matches!(
+        escape_value("plain-text"),
+        Cow::Borrowed("plain-text")
+    ));
+    assert!(matches!(escape_value(""), Cow::Borrowed("")));
+    assert!(matches!(escape_value("a b"), Cow::Owned(_)));
+}
+
+#[test]
+fn test_escape_writers() {
+    let mut fmt_out = String::new();
+    escape_value_fmt("a;b c\\d\re\nf", &mut fmt_out).unwrap();
+    assert_eq!(fmt_out, r"a\:b\sc\\d\re\nf");
+
+    let mut io_out = Vec::new();
+    escape_value_io("a;b c\\d\re\nf", &mut io_out).unwrap();
+    assert_eq!(io_out, br"a\:b\sc\\d\re\nf");
+}
+
+proptest! {
+    #[test]
+    fn escape_round_trip(value in any::<String>()) {
+        let escaped = escape_value(&value);
+        prop_assert_eq!(unescaped_to_escaped(&escaped), value);
+    }
+
+    #[test]
+    fn escape_round_trip_special(value in "[a-z;\\\\ \r\n:srn]*") {
+        let escaped = escape_value(&value);
+        prop_assert!(!escaped.contains([';', ' ', '\r', '\n']));
+        prop_assert_eq!(unescaped_to_escaped(&escaped), value);
+    }
+
+    #[test]
+    fn escape_writers_agree(value in any::<String>()) {
+        let mut fmt_out = String::new();
+        escape_value_fmt(&value, &mut fmt_out).unwrap();
+
+        let mut io_out = Vec::new();
+        escape_value_io(&value, &mut io_out).unwrap();
+
+        prop_assert_eq!(&fmt_out, &escape_value(&value));
+        prop_assert_eq!(io_out, fmt_out.into_bytes());
+    }
+}
@@ -1,304 +1,754 @@
-// use ircv3_tags::host::{standard_host_validate, try_host, HostError};
-//
-// #[test]
-// fn base() {
-//     let input = "example.com";
-//     let (remain, messages) = ircv3_tags::host::try_host(input).unwrap();
-//     assert_eq!(remain, "");
-//     assert_eq!(messages, "example.com");
-//
-//     assert_eq!(
-//         try_host(""),
-//         Err(nom::Err::Error(HostError::new(
-//             "",
-//             nom::error::ErrorKind::Char,
-//             ircv3_tags::ErrorKind::Empty,
-//             "label must not be empty",
-//         )))
-//     );
-//
-//     let inputs = ["-", "0", " "];
-//
-//     for input in inputs {
-//         assert_eq!(
-//             try_host(input),
-//             Err(nom::Err::Error(HostError::new(
-//                 input,
-//                 nom::error::ErrorKind::Char,
-//                 ircv3_tags::ErrorKind::Host,
-//                 "label must start with an allowed character",
-//             )))
-//         );
-//     }
-//
-//     assert_eq!(
-//         try_host("a-"),
-//         Err(nom::Err::Error(HostError {
-//             input: "a-",
-//             code: nom::error::ErrorKind::Char,
-//             error: ircv3_tags::ErrorKind::Host,
-//             reason: "label contains an invalid chracter",
-//         }))
-//     );
-//
-//     assert_eq!(
-//         try_host("a--b"),
-//         Err(nom::Err::Error(HostError {
-//             input: "a--b",
-//             code: nom::error::ErrorKind::Char,
-//             error: ircv3_tags::ErrorKind::Host,
-//             reason: "label contains an invalid chracter",
-//         }))
-//     );
-//
-//     assert_eq!(
-//         ircv3_tags::host::try_host("invalid-"),
-//         Err(nom::Err::Error(ircv3_tags::host::HostError::new(
-//             "invalid-",
-//             nom::error::ErrorKind::Char,
-//             ircv3_tags::ErrorKind::Host,
-//             "label contains an invalid chracter",
-//         )))
-//     );
-// }
-//
-// #[test]
-// fn standard_host_validate_host() {
-//     assert!(standard_host_validate("example"));
-//     assert!(standard_host_validate("example.com"));
-//     assert!(standard_host_validate("example123.com"));
-//     assert!(standard_host_validate("example.com123"));
-//     assert!(standard_host_validate("exam123ple.com"));
-//     assert!(standard_host_validate("example.c123om"));
-//     assert!(standard_host_validate("examp123le.c123om"));
-//     assert!(standard_host_validate("exam-ple.com"));
-//     assert!(standard_host_validate("example.c-om"));
-//     assert!(standard_host_validate("sub.example.com"));
-//     assert!(!standard_host_validate("example-.com"));
-//     assert!(!standard_host_validate("-example.com"));
-//     assert!(!standard_host_validate("example.-com"));
-//     assert!(!standard_host_validate("example.com-"));
-//     assert!(!standard_host_validate("exam--ple.com"));
-//     assert!(!standard_host_validate("example.c--om"));
-//     assert!(!standard_host_validate("exam_ple.com"));
-//     assert!(!standard_host_validate("exam__ple.com"));
-//     assert!(!standard_host_validate("example.c_om"));
-//     assert!(!standard_host_validate("example..com"));
-//     assert!(!standard_host_validate("example..com"));
-// }
-//
-// macro_rules! fn_test {
-//         ($name:ident, $fn:expr, [$($test:literal),+$(,)?]) => {
-//             #[test]
-//             fn $name(){
-//                 let inputs = [$($test),+];
-//
-//                 for input in inputs {
-//                     assert!($fn(input).is_ok(), "{}", input);
-//                     let (remain, _) = $fn(input).unwrap();
-//                     assert!(remain.is_empty());
-//                 }
-//             }
-//         };
-//
-//         (err, $name:ident, $fn:expr, [$($test:literal),+$(,)?]) => {
-//             #[test]
-//             fn $name(){
-//                 let inputs = [$($test),+];
-//
-//                 for input in inputs {
-//                     assert!($fn(input).is_err(), "'{}'", input);
-//                 }
-//             }
-//         };
-//
-//     }
-//
-// macro_rules! fn_tests {
-//         ($fn:expr) => {
-//             fn_test!(
-//                 simple,
-//                 $fn,
-//                 [
-//                     "example.com",
-//                     "host.a.z",
-//                     "server.host1",
-//                     "a-b-c.x-y-z123",
-//                 ]
-//             );
-//             fn_test!(
-//                 normal,
-//                 $fn,
-//                 [
-//                     "my-host-name",
-//                     "my-host-name.server-01",
-//                     "web-server-prod",
-//                     "app-server-1.database-cluster-a"
-//                 ]
-//             );
-//             fn_test!(edge_cases,$fn,[
-//                 "a-very-long-hostname-that-is-still-valid-according-to-rfc952-specs-with-multiple-hyphens",
-//                 "a-b",
-//                 "x1",
-//                 "a-1"
-//             ]);
-//             fn_test!(
-//                 max_length,
-//                 $fn,
-//                 ["abcdefghijklmnopqrstuvwxyz-0123456789-abcdefghijklmnopqrstuvwxyz"]
-//             );
-//         };
-//         ($fn:expr, err) => {
-//             fn_test!(
-//                 err,
-//                 starts_with_digit,
-//                 $fn,
-//                 [
-//                     "1host",
-//                     "host.1host",
-//                     "9server",
-//                     "123-invalid",
-//                     "0invalid"
-//                 ]
-//             );
-//             fn_test!(
-//                 err,
-//                 starts_with_hyphen,
-//                 $fn,
-//                 [
-//                     "-host",
-//                     "host.-host",
-//                     "-server-1",
-//                     "-invalid-name"
-//                 ]
-//             );
-//             fn_test!(
-//                 err,
-//                 ends_with_hyphen,
-//                 $fn,
-//                 [
-//                     "host-",
-//                     "host.host-",
-//                     "server-name-",
-//                     "server.server-name-",
-//                     "invalid-"
-//                 ]
-//             );
-//             fn_test!(
-//                 err,
-//                 consecutive_hyphens,
-//                 $fn,
-//                 [
-//                     "host--name",
-//                     "host.host--name",
-//                     "server--01",
-//                     "double--hyphen--test",
-//                     "triple---hyphen",
-//                     "quadruple----hyphen"
-//                 ]
-//             );
-//             fn_test!(err, empty, $fn, [""]);
-//             fn_test!(err, whitespace_only, $fn, [" ", "  ", "\t", "\n"]);
-//         };
-//         ($fn:expr, allow) => {
-//             fn_test!(
-//                 simple,
-//                 $fn,
-//                 [
-//                     "example.com",
-//                     "host.a.z",
-//                     "server.host1",
-//                     "a-b-c.x-y-z123",
-//                     "a-b-c.x-y-z123",
-//                 ]
-//             );
-//             fn_test!(
-//                 normal,
-//                 $fn,
-//                 [
-//                     "my-host-name",
-//                     "my-host-name.server-01",
-//                     "web-server-prod",
-//                     "app-server-1.database-cluster-a"
-//                 ]
-//             );
-//             fn_test!(edge_cases,$fn,[
-//                 "a-very-long-hostname-that-is-still-valid-according-to-rfc952-specs-with-multiple-hyphens",
-//                 "a-b",
-//                 "x1",
-//                 "a-1"
-//             ]);
-//             fn_test!(
-//                 max_length,
-//                 $fn,
-//                 ["abcdefghijklmnopqrstuvwxyz-0123456789-abcdefghijklmnopqrstuvwxyz"]
-//             );
-//         };
-//         ($fn:expr, err, allow) => {
-//             fn_test!(
-//                 err,
-//                 starts_with_digit,
-//                 $fn,
-//                 [
-//                     "1host",
-//                     "host.1host",
-//                     "9server",
-//                     "123-invalid",
-//                     "0invalid"
-//                 ]
-//             );
-//             fn_test!(
-//                 err,
-//                 starts_with_hyphen,
-//                 $fn,
-//                 [
-//                     "-host",
-//                     "host.-host",
-//                     "-server-1",
-//                     "-invalid-name"
-//                 ]
-//             );
-//             fn_test!(
-//                 err,
-//                 ends_with_hyphen,
-//                 $fn,
-//                 [
-//                     "host-",
-//                     "host.host-",
-//                     "server-name-",
-//                     "server.server-name-",
-//                     "invalid-"
-//                 ]
-//             );
-//             fn_test!(
-//                 err,
-//                 ends_with_underdash,
-//                 $fn,
-//                 [
-//                     "host_",
-//                     "host.host_",
-//                     "server-name_",
-//                     "server.server-name_",
-//                     "invalid_"
-//                 ]
-//             );
-//             fn_test!(
-//                 err,
-//                 consecutive_hyphens,
-//                 $fn,
-//                 [
-//                     "host--name",
-//                     "host.host--name",
-//                     "server--01",
-//                     "double--hyphen--test",
-//                     "triple---hyphen",
-//                     "quadruple----hyphen"
-//                 ]
-//             );
-//             fn_test!(err, empty, $fn, [""]);
-//             fn_test!(err, whitespace_only, $fn, [" ", "  ", "\t", "\n"]);
-//         }
-//
-//     }
-// fn_tests!(ircv3_tags::host::host);
-// fn_tests!(ircv3_tags::host::host, err);
+#![allow(deprecated)]
+
+use ircv3_tags::host::{
+    standard_host_validate, try_host, try_irc_host, CloakSyntax, Host, HostError, HostKind,
+    IdnHostValidator, IrcHostParser, RFC1123HostValidator, RFC952HostParser,
+};
+
+#[test]
+fn base() {
+    let input = "example.com";
+    let (remain, messages) = ircv3_tags::host::try_host(input).unwrap();
+    assert_eq!(remain, "");
+    assert_eq!(messages, "example.com");
+
+    assert_eq!(
+        try_host(""),
+        Err(nom::Err::Error(HostError::new(
+            "",
+            ircv3_tags::ErrorKind::Empty,
+            "label must not be empty",
+        )))
+    );
+
+    let inputs = ["-", "0", " "];
+
+    for input in inputs {
+        assert_eq!(
+            try_host(input),
+            Err(nom::Err::Error(HostError::new(
+                input,
+                ircv3_tags::ErrorKind::Host,
+                "label must start with an allowed character",
+            )))
+        );
+    }
+
+    assert_eq!(
+        try_host("a-"),
+        Err(nom::Err::Error(HostError {
+            input: "a-",
+            error: ircv3_tags::ErrorKind::Host,
+            reason: "label contains an invalid character",
+        }))
+    );
+
+    assert_eq!(
+        try_host("a--b"),
+        Err(nom::Err::Error(HostError {
+            input: "a--b",
+            error: ircv3_tags::ErrorKind::Host,
+            reason: "label contains an invalid character",
+        }))
+    );
+
+    assert_eq!(
+        ircv3_tags::host::try_host("invalid-"),
+        Err(nom::Err::Error(ircv3_tags::host::HostError::new(
+            "invalid-",
+            ircv3_tags::ErrorKind::Host,
+            "label contains an invalid character",
+        )))
+    );
+}
+
+#[test]
+fn standard_host_validate_host() {
+    assert!(standard_host_validate("example"));
+    assert!(standard_host_validate("example.com"));
+    assert!(standard_host_validate("example123.com"));
+    assert!(standard_host_validate("example.com123"));
+    assert!(standard_host_validate("exam123ple.com"));
+    assert!(standard_host_validate("example.c123om"));
+    assert!(standard_host_validate("examp123le.c123om"));
+    assert!(standard_host_validate("exam-ple.com"));
+    assert!(standard_host_validate("example.c-om"));
+    assert!(standard_host_validate("sub.example.com"));
+    assert!(!standard_host_validate("example-.com"));
+    assert!(!standard_host_validate("-example.com"));
+    assert!(!standard_host_validate("example.-com"));
+    assert!(!standard_host_validate("example.com-"));
+    assert!(!standard_host_validate("exam--ple.com"));
+    assert!(!standard_host_validate("example.c--om"));
+    assert!(!standard_host_validate("exam_ple.com"));
+    assert!(!standard_host_validate("exam__ple.com"));
+    assert!(!standard_host_validate("example.c_om"));
+    assert!(!standard_host_validate("example..com"));
+    assert!(!standard_host_validate("example..com"));
+    assert!(standard_host_validate("xn--bcher-kva.example"));
+    assert!(standard_host_validate("www.XN--bcher-kva.example"));
+    assert!(!standard_host_validate("xn--.example"));
+    assert!(!standard_host_validate("xn--a--b.example"));
+    assert!(!standard_host_validate("axn--b.example"));
+}
+
+#[test]
+fn irc_host_kinds() {
+    assert_eq!(
+        try_irc_host("192.0.2.1 PRIVMSG"),
+        Ok((" PRIVMSG", HostKind::Ipv4("192.0.2.1".parse().unwrap())))
+    );
+    assert_eq!(
+        try_irc_host("2001:db8::1"),
+        Ok(("", HostKind::Ipv6("2001:db8::1".parse().unwrap())))
+    );
+    assert_eq!(
+        try_irc_host("0::1"),
+        Ok(("", HostKind::Ipv6("::1".parse().unwrap())))
+    );
+    assert_eq!(
+        try_irc_host("0::ffff:192.0.2.1"),
+        Ok(("", HostKind::Ipv6("::ffff:192.0.2.1".parse().unwrap())))
+    );
+    assert_eq!(
+        try_irc_host("user/foo/bar"),
+        Ok(("", HostKind::Cloak("user/foo/bar")))
+    );
+    assert_eq!(
+        try_irc_host("gateway/web/irccloud.com/x-abc"),
+        Ok(("", HostKind::Cloak("gateway/web/irccloud.com/x-abc")))
+    );
+    assert_eq!(
+        try_irc_host("example.com"),
+        Ok(("", HostKind::Hostname("example.com")))
+    );
+}
+
+#[test]
+fn irc_host_errors() {
+    assert_eq!(
+        try_irc_host("2001:db8:::1"),
+        Err(nom::Err::Error(HostError::new(
+            "2001:db8:::1",
+            ircv3_tags::ErrorKind::Host,
+            "invalid IPv6 address",
+        )))
+    );
+    assert_eq!(
+        try_irc_host("user//bar"),
+        Err(nom::Err::Error(HostError::new(
+            "/bar",
+            ircv3_tags::ErrorKind::Empty,
+            "cloak segment must not be empty",
+        )))
+    );
+    assert_eq!(
+        try_irc_host("user/f*o"),
+        Err(nom::Err::Error(HostError::new(
+            "*o",
+            ircv3_tags::ErrorKind::Host,
+            "cloak contains an invalid character",
+        )))
+    );
+    assert_eq!(
+        try_irc_host("example.com#x"),
+        Err(nom::Err::Error(HostError::new(
+            "#x",
+            ircv3_tags::ErrorKind::Host,
+            "host contains an invalid character",
+        )))
+    );
+    assert!(try_irc_host("192.0.2.300").is_err());
+}
+
+#[test]
+fn irc_host_cloak_syntax() {
+    let parser = IrcHostParser::default().cloak(None);
+    assert!(parser.try_host("user/foo").is_err());
+
+    let parser =
+        IrcHostParser::default().cloak(Some(CloakSyntax::new().separator(':').allow_chars(&['*'])));
+    assert_eq!(
+        parser.try_host("user:f*o"),
+        Ok(("", HostKind::Cloak("user:f*o")))
+    );
+    assert!(parser.try_host("user/foo").is_err());
+}
+
+#[test]
+fn host_labels() {
+    let host = Host::new("irc.example.com.");
+    assert_eq!(host.labels().collect::<Vec<_>>(), ["irc", "example", "com"]);
+    assert_eq!(host.labels().next_back(), Some("com"));
+    assert_eq!(host.tld(), Some("com"));
+    assert_eq!(host.as_str(), "irc.example.com.");
+    assert_eq!(host.to_string(), "irc.example.com.");
+
+    assert_eq!(Host::new("localhost").tld(), None);
+
+    let (_, host) = try_host("tmi.twitch.tv PRIVMSG").unwrap();
+    assert_eq!(host.labels().count(), 3);
+    assert_eq!(host.tld(), Some("tv"));
+}
+
+#[test]
+fn host_suffix() {
+    let host = Host::new("tmi.Twitch.TV");
+    assert!(host.has_suffix("twitch.tv"));
+    assert!(host.has_suffix("tv."));
+    assert!(host.has_suffix("tmi.twitch.tv"));
+    assert!(!host.has_suffix("witch.tv"));
+    assert!(!host.has_suffix("a.tmi.twitch.tv"));
+
+    assert!(host.is_subdomain_of("twitch.tv"));
+    assert!(!host.is_subdomain_of("tmi.twitch.tv"));
+    assert!(!Host::new("nottwitch.tv").is_subdomain_of("twitch.tv"));
+
+    assert!(host.matches("*.twitch.tv"));
+    assert!(host.matches("TMI.twitch.tv."));
+    assert!(!host.matches("*.tmi.twitch.tv"));
+    assert!(!Host::new("twitch.tv").matches("*.twitch.tv"));
+}
+
+#[test]
+fn host_eq_hash() {
+    use std::collections::HashSet;
+
+    assert_eq!(Host::new("Example.COM"), Host::new("example.com."));
+    assert_eq!(Host::new("example.com."), "EXAMPLE.com");
+    assert_ne!(Host::new("example.com"), Host::new("example.co"));
+    assert_ne!(Host::new("example.com"), Host::new("example.com.."));
+
+    let allowlist: HashSet<Host> = ["twitch.tv", "Example.com."]
+        .into_iter()
+        .map(Host::new)
+        .collect();
+    assert!(allowlist.contains(&Host::new("TWITCH.tv.")));
+    assert!(allowlist.contains(&Host::new("example.com")));
+    assert!(!allowlist.contains(&Host::new("tmi.twitch.tv")));
+
+    assert_eq!(
+        Host::new("bücher.example"),
+        Host::new("xn--bcher-kva.example")
+    );
+    assert_eq!(Host::new("BÜCHER.example."), "XN--bcher-kva.example");
+    assert!(Host::new("www.bücher.example").has_suffix("xn--bcher-kva.example"));
+    assert!(Host::new("www.xn--bcher-kva.example").has_suffix("bücher.example"));
+
+    let allowlist: HashSet<Host> = ["bücher.example"].into_iter().map(Host::new).collect();
+    assert!(allowlist.contains(&Host::new("xn--bcher-kva.example.")));
+}
+
+#[test]
+fn rfc1123_host() {
+    let parser = RFC952HostParser::new(RFC1123HostValidator);
+
+    for input in [
+        "3com.com",
+        "1password.com",
+        "xn--bcher-kva.example",
+        "a.b-c.d1",
+    ] {
+        assert_eq!(
+            parser.try_host(input),
+            Ok(("", Host::new(input))),
+            "{}",
+            input
+        );
+    }
+    assert_eq!(
+        parser.try_host("example.com."),
+        Ok(("", Host::new("example.com.")))
+    );
+    assert_eq!(
+        parser.try_host("example.com./foo"),
+        Ok(("/foo", Host::new("example.com.")))
+    );
+
+    for input in [
+        "-example.com",
+        "example-.com",
+        "exa_mple.com",
+        "example..com",
+        ".",
+    ] {
+        assert!(!RFC1123HostValidator.validate_host(input), "{}", input);
+    }
+}
+
+#[test]
+fn rfc1123_host_limits() {
+    let parser = RFC952HostParser::new(RFC1123HostValidator);
+
+    let label = "a".repeat(63);
+    assert_eq!(parser.try_host(&label), Ok(("", Host::new(&label))));
+
+    let label = "a".repeat(64);
+    let input = format!("example.{}.com", label);
+    assert_eq!(
+        parser.try_host(&input),
+        Err(nom::Err::Error(HostError::new(
+            &input[8..],
+            ircv3_tags::ErrorKind::Host,
+            "label exceeds the maximum length",
+        )))
+    );
+
+    // 4 * 63 + 3 dots = 255 bytes
+    let long = [&"a".repeat(63)[..]; 4].join(".");
+    assert_eq!(
+        parser.try_host(&long),
+        Err(nom::Err::Error(HostError::new(
+            long.as_str(),
+            ircv3_tags::ErrorKind::Host,
+            "host exceeds the maximum length",
+        )))
+    );
+
+    // 253 bytes, the trailing dot is not counted
+    let max = format!("{}.{}", &long[..189], "a".repeat(63));
+    assert_eq!(max.len(), 253);
+    assert!(RFC1123HostValidator.validate_host(&max));
+    assert!(RFC1123HostValidator.validate_host(&format!("{}.", max)));
+    assert!(!RFC1123HostValidator.validate_host(&format!("a{}", max)));
+}
+
+#[test]
+fn rfc1123_vendor_prefix() {
+    use ircv3_tags::tags::IRCv3TagsParser;
+
+    let input = "@3com.com/foo=bar;1password.com/tag :nick PRIVMSG #channel :Hello";
+    assert!(IRCv3TagsParser::default().try_parse(input).is_err());
+
+    let parser = IRCv3TagsParser::default().host_validator(RFC1123HostValidator);
+    let (remain, tags) = parser.parse(input);
+    assert_eq!(remain, ":nick PRIVMSG #channel :Hello");
+    assert_eq!(tags.get("3com.com/foo"), Some("bar"));
+    assert_eq!(tags.get("1password.com/tag"), Some(""));
+}
+
+#[test]
+fn idn_host() {
+    let parser = RFC952HostParser::new(IdnHostValidator);
+
+    for input in [
+        "bücher.example",
+        "例え.テスト",
+        "xn--bcher-kva.example",
+        "münchen.de",
+        "a\u{301}b.com",
+        "bu\u{308}cher.example",
+    ] {
+        assert_eq!(
+            parser.try_host(input),
+            Ok(("", Host::new(input))),
+            "{}",
+            input
+        );
+        assert!(IdnHostValidator.validate_host(input), "{}", input);
+    }
+
+    for input in [
+        "-bücher.example",
+        "bücher-.example",
+        "bü_cher.example",
+        "xn--a.example",
+        "a\u{3002}b.example",
+    ] {
+        assert!(!IdnHostValidator.validate_host(input), "{}", input);
+    }
+
+    let label = "ü".repeat(60);
+    assert!(!IdnHostValidator.validate_host(&label));
+}
+
+#[test]
+fn idn_host_conversion() {
+    assert_eq!(
+        IdnHostValidator.to_ascii("bücher.example"),
+        Ok("xn--bcher-kva.example".to_string())
+    );
+    assert_eq!(
+        IdnHostValidator.to_ascii("BÜCHER.Example"),
+        IdnHostValidator.to_ascii("xn--bcher-kva.example")
+    );
+    assert_eq!(
+        IdnHostValidator.to_ascii("example.com"),
+        Ok("example.com".to_string())
+    );
+    assert_eq!(
+        IdnHostValidator.to_ascii("bü_cher.example"),
+        Err(HostError::new(
+            "bü_cher.example",
+            ircv3_tags::ErrorKind::Host,
+            "invalid internationalized host",
+        ))
+    );
+
+    assert_eq!(
+        IdnHostValidator.to_unicode("xn--bcher-kva.example"),
+        "bücher.example"
+    );
+    assert_eq!(IdnHostValidator.to_unicode("example.com"), "example.com");
+
+    assert_eq!(
+        Host::new("bu\u{308}cher.example").to_ascii(),
+        "xn--bcher-kva.example"
+    );
+    assert_eq!(
+        Host::new("bücher.example.").to_ascii(),
+        "xn--bcher-kva.example."
+    );
+    assert_eq!(Host::new("Example.com").to_ascii(), "Example.com");
+}
+
+#[test]
+fn vendor_case_folding() {
+    use std::collections::HashSet;
+
+    use ircv3_tags::{tags::DuplicateKeyPolicy, tags::IRCv3TagsParser, TagKey};
+
+    // Vendors compare case-insensitively whether they are ASCII or internationalized,
+    // key names stay case-sensitive.
+    assert_eq!(TagKey::new("Example.com/t"), TagKey::new("example.com/t"));
+    assert_eq!(TagKey::new("+EXAMPLE.com/t"), "+example.com/t");
+    assert_eq!(
+        TagKey::new("Bücher.example/t"),
+        TagKey::new("bücher.example/t")
+    );
+    assert_eq!(TagKey::new("Bücher.example/t"), "XN--bcher-kva.example/t");
+    assert_ne!(TagKey::new("example.com/T"), TagKey::new("example.com/t"));
+    assert_ne!(TagKey::new("T"), TagKey::new("t"));
+
+    let keys = [
+        "Example.com/t",
+        "example.com/t",
+        "Bücher.example/t",
+        "bücher.example/t",
+    ]
+    .map(TagKey::new)
+    .into_iter()
+    .collect::<HashSet<_>>();
+    assert_eq!(keys.len(), 2);
+
+    let parser = IRCv3TagsParser::default().host_validator(IdnHostValidator);
+    let (_, tags) = parser.parse("@Example.com/t=1;Bücher.example/t=2 :rest");
+    assert_eq!(tags.get("example.com/t"), Some("1"));
+    assert_eq!(tags.get("bücher.example/t"), Some("2"));
+
+    // A trailing dot is ignored, like in `Host` equality
+    assert_eq!(TagKey::new("example.com./t"), TagKey::new("example.com/t"));
+    assert_eq!(
+        TagKey::new("+Bücher.example./t"),
+        "+xn--bcher-kva.example/t"
+    );
+    assert_ne!(TagKey::new("example.com../t"), TagKey::new("example.com/t"));
+    let parser = IRCv3TagsParser::default().host_validator(RFC1123HostValidator);
+    let (_, tags) = parser.parse("@example.com./a=1 :rest");
+    assert_eq!(tags.get("example.com/a"), Some("1"));
+    assert_eq!(tags.get("Example.com./a"), Some("1"));
+    assert_eq!(
+        Host::new("example.com.") == Host::new("example.com"),
+        TagKey::new("example.com./a") == TagKey::new("example.com/a")
+    );
+
+    let parser = parser.duplicate_policy(DuplicateKeyPolicy::Reject);
+    assert!(parser
+        .try_parse("@Example.com/t=1;example.com/t=2 :rest")
+        .is_err());
+}
+
+#[test]
+fn idn_vendor_prefix() {
+    use ircv3_tags::{tags::IRCv3TagsParser, IRCv3TagsBuf, TagKey};
+
+    let input = "@bücher.example/tag=1;+xn--bcher-kva.example/foo :nick PRIVMSG #channel :Hello";
+    assert!(IRCv3TagsParser::default().try_parse(input).is_err());
+
+    let parser = IRCv3TagsParser::default().host_validator(IdnHostValidator);
+    let (_, tags) = parser.parse(input);
+    assert_eq!(tags.get("bücher.example/tag"), Some("1"));
+    assert_eq!(tags.get("xn--bcher-kva.example/tag"), Some("1"));
+    assert!(tags.contains_key("+bücher.example/foo"));
+    assert_eq!(
+        tags.to_wire(),
+        "@xn--bcher-kva.example/tag=1;+xn--bcher-kva.example/foo "
+    );
+
+    // A-labels written back parse with the default parser
+    let wire = tags.to_wire();
+    let (remain, reparsed) = ircv3_tags::parse(&wire);
+    assert_eq!(remain, "");
+    assert_eq!(reparsed.get("bücher.example/tag"), Some("1"));
+    assert!(reparsed.contains_key("+bücher.example/foo"));
+    assert_eq!(
+        ircv3_tags::serialize([("bücher.example/tag", Some("a b"))]).unwrap(),
+        "@xn--bcher-kva.example/tag=a\\sb "
+    );
+
+    let keys = tags.keys().collect::<Vec<_>>();
+    assert_eq!(keys[0].vendor(), Some("bücher.example"));
+    assert_eq!(keys[0].to_ascii(), "xn--bcher-kva.example/tag");
+    assert_eq!(keys[1].to_unicode(), "+bücher.example/foo");
+    assert_eq!(keys[1].to_ascii(), "+xn--bcher-kva.example/foo");
+    assert_eq!(keys[0], TagKey::new("xn--bcher-kva.example/tag"));
+    assert_ne!(keys[0], TagKey::new("xn--bcher-kva.example/foo"));
+
+    let mut buf = IRCv3TagsBuf::new();
+    for key in tags.keys() {
+        buf.insert(key.to_ascii(), tags.get(key)).unwrap();
+    }
+    assert_eq!(
+        buf.to_wire(),
+        "@xn--bcher-kva.example/tag=1;+xn--bcher-kva.example/foo= "
+    );
+}
+
+macro_rules! fn_test {
+        ($name:ident, $fn:expr, [$($test:literal),+$(,)?]) => {
+            #[test]
+            fn $name(){
+                let inputs = [$($test),+];
+
+                for input in inputs {
+                    assert!($fn(input).is_ok(), "{}", input);
+                    let (remain, _) = $fn(input).unwrap();
+                    assert!(remain.is_empty());
+                }
+            }
+        };
+
+        (err, $name:ident, $fn:expr, [$($test:literal),+$(,)?]) => {
+            #[test]
+            fn $name(){
+                let inputs = [$($test),+];
+
+                for input in inputs {
+                    assert!($fn(input).is_err(), "'{}'", input);
+                }
+            }
+        };
+
+    }
+
+macro_rules! fn_tests {
+        ($fn:expr) => {
+            fn_test!(
+                simple,
+                $fn,
+                [
+                    "example.com",
+                    "host.a.z",
+                    "server.host1",
+                    "a-b-c.x-y-z123",
+                ]
+            );
+            fn_test!(
+                normal,
+                $fn,
+                [
+                    "my-host-name",
+                    "my-host-name.server-01",
+                    "web-server-prod",
+                    "app-server-1.database-cluster-a"
+                ]
+            );
+            fn_test!(edge_cases,$fn,[
+                "a-very-long-hostname-that-is-still-valid-according-to-rfc952-specs-with-multiple-hyphens",
+                "a-b",
+                "x1",
+                "a-1"
+            ]);
+            fn_test!(
+                max_length,
+                $fn,
+                ["abcdefghijklmnopqrstuvwxyz-0123456789-abcdefghijklmnopqrstuvwxyz"]
+            );
+        };
+        ($fn:expr, err) => {
+            fn_test!(
+                err,
+                starts_with_digit,
+                $fn,
+                [
+                    "1host",
+                    "host.1host",
+                    "9server",
+                    "123-invalid",
+                    "0invalid"
+                ]
+            );
+            fn_test!(
+                err,
+                starts_with_hyphen,
+                $fn,
+                [
+                    "-host",
+                    "host.-host",
+                    "-server-1",
+                    "-invalid-name"
+                ]
+            );
+            fn_test!(
+                err,
+                ends_with_hyphen,
+                $fn,
+                [
+                    "host-",
+                    "host.host-",
+                    "server-name-",
+                    "server.server-name-",
+                    "invalid-"
+                ]
+            );
+            fn_test!(
+                err,
+                consecutive_hyphens,
+                $fn,
+                [
+                    "host--name",
+                    "host.host--name",
+                    "server--01",
+                    "double--hyphen--test",
+                    "triple---hyphen",
+                    "quadruple----hyphen"
+                ]
+            );
+            fn_test!(err, empty, $fn, [""]);
+            fn_test!(err, whitespace_only, $fn, [" ", "  ", "\t", "\n"]);
+        };
+        ($fn:expr, allow) => {
+            fn_test!(
+                simple,
+                $fn,
+                [
+                    "example.com",
+                    "host.a.z",
+                    "server.host1",
+                    "a-b-c.x-y-z123",
+                    "a-b-c.x-y-z123",
+                ]
+            );
+            fn_test!(
+                normal,
+                $fn,
+                [
+                    "my-host-name",
+                    "my-host-name.server-01",
+                    "web-server-prod",
+                    "app-server-1.database-cluster-a"
+                ]
+            );
+            fn_test!(edge_cases,$fn,[
+                "a-very-long-hostname-that-is-still-valid-according-to-rfc952-specs-with-multiple-hyphens",
+                "a-b",
+                "x1",
+                "a-1"
+            ]);
+            fn_test!(
+                max_length,
+                $fn,
+                ["abcdefghijklmnopqrstuvwxyz-0123456789-abcdefghijklmnopqrstuvwxyz"]
+            );
+        };
+        ($fn:expr, err, allow) => {
+            fn_test!(
+                err,
+                starts_with_digit,
+                $fn,
+                [
+                    "1host",
+                    "host.1host",
+                    "9server",
+                    "123-invalid",
+                    "0invalid"
+                ]
+            );
+            fn_test!(
+                err,
+                starts_with_hyphen,
+                $fn,
+                [
+                    "-host",
+                    "host.-host",
+                    "-server-1",
+                    "-invalid-name"
+                ]
+            );
+            fn_test!(
+                err,
+                ends_with_hyphen,
+                $fn,
+                [
+                    "host-",
+                    "host.host-",
+                    "server-name-",
+                    "server.server-name-",
+                    "invalid-"
+                ]
+            );
+            fn_test!(
+                err,
+                ends_with_underdash,
+                $fn,
+                [
+                    "host_",
+                    "host.host_",
+                    "server-name_",
+                    "server.server-name_",
+                    "invalid_"
+                ]
+            );
+            fn_test!(
+                err,
+                consecutive_hyphens,
+                $fn,
+                [
+                    "host--name",
+                    "host.host--name",
+                    "server--01",
+                    "double--hyphen--test",
+                    "triple---hyphen",
+                    "quadruple----hyphen"
+                ]
+            );
+            fn_test!(err, empty, $fn, [""]);
+            fn_test!(err, whitespace_only, $fn, [" ", "  ", "\t", "\n"]);
+        }
+
+    }
+fn_tests!(ircv3_tags::host::host);
+fn_tests!(ircv3_tags::host::host, err);
+
+#[test]
+fn custom_host_validator() {
+    use ircv3_tags::{tags::IRCv3TagsParser, CharValidator, HostValidator};
+
+    /// Short ASCII labels only, for a network that uses them as vendors.
+    struct ShortLabels;
+    impl CharValidator for ShortLabels {
+        fn is_valid_char(&self, c: char) -> bool {
+            c.is_ascii_lowercase()
+        }
+        fn is_valid_start_char(&self, c: char) -> bool {
+            c.is_ascii_lowercase()
+        }
+    }
+    impl HostValidator for ShortLabels {
+        fn max_label_len(&self) -> Option<usize> {
+            Some(4)
+        }
+        fn allow_trailing_dot(&self) -> bool {
+            true
+        }
+    }
+
+    let parser = RFC952HostParser::new(ShortLabels);
+    assert_eq!(parser.try_host("abcd.ef."), Ok(("", Host::new("abcd.ef."))));
+    assert!(parser.try_host("abcde.ef").is_err());
+
+    let parser = IRCv3TagsParser::default().host_validator(ShortLabels);
+    let (_, tags) = parser.try_parse("@abcd.ef/tag=1 :rest").unwrap();
+    assert_eq!(tags.get("abcd.ef/tag"), Some("1"));
+    assert!(parser.try_parse("@abcde.ef/tag=1 :rest").is_err());
+}
@@ -0,0 +1,210 @@
+#![allow(deprecated)]
+
+use ircv3_tags::{
+    message::{message, try_message, MessageError, MessageParser},
+    source::Source,
+    tags::IRCv3TagsParser,
+    ErrorKind,
+};
+
+#[test]
+fn full_message() {
+    let input =
+        "@id=123;time=2025-05-04T12:34:56Z :nick!user@host.com PRIVMSG #channel :Hello, world!";
+    let (remain, msg) = try_message(input).unwrap();
+
+    assert_eq!(remain, "");
+    assert_eq!(msg.tags.get("id"), Some("123"));
+    assert_eq!(msg.tags.get("time"), Some("2025-05-04T12:34:56Z"));
+    assert_eq!(
+        msg.source,
+        Some(Source::User {
+            nick: "nick",
+            user: Some("user"),
+            host: Some("host.com")
+        })
+    );
+    assert_eq!(msg.command, "PRIVMSG");
+    assert_eq!(msg.params, ["#channel"]);
+    assert_eq!(msg.trailing, Some("Hello, world!"));
+    assert!(!msg.is_numeric());
+    assert_eq!(
+        msg.all_params().collect::<Vec<_>>(),
+        ["#channel", "Hello, world!"]
+    );
+}
+
+#[test]
+fn without_tags_or_source() {
+    let (remain, msg) = try_message("PING :irc.example.com").unwrap();
+    assert_eq!(remain, "");
+    assert!(msg.tags.0.is_empty());
+    assert_eq!(msg.source, None);
+    assert_eq!(msg.command, "PING");
+    assert!(msg.params.is_empty());
+    assert_eq!(msg.trailing, Some("irc.example.com"));
+
+    let (_, msg) = try_message(":irc.example.com NOTICE * :*** Looking up your hostname").unwrap();
+    assert_eq!(msg.source, Some(Source::Server("irc.example.com")));
+    assert_eq!(msg.command, "NOTICE");
+    assert_eq!(msg.params, ["*"]);
+    assert_eq!(msg.trailing, Some("*** Looking up your hostname"));
+
+    let (_, msg) = try_message("@+typing=active TAGMSG #channel").unwrap();
+    assert_eq!(msg.tags.get("+typing"), Some("active"));
+    assert_eq!(msg.source, None);
+    assert_eq!(msg.command, "TAGMSG");
+    assert_eq!(msg.params, ["#channel"]);
+    assert_eq!(msg.trailing, None);
+}
+
+#[test]
+fn numerics() {
+    let input = ":irc.example.com 001 nick :Welcome to the Internet Relay Network nick";
+    let (_, msg) = try_message(input).unwrap();
+    assert_eq!(msg.command, "001");
+    assert!(msg.is_numeric());
+    assert_eq!(msg.params, ["nick"]);
+
+    let input = ":irc.example.com 353 nick = #channel :nick @op +voice";
+    let (_, msg) = try_message(input).unwrap();
+    assert_eq!(msg.command, "353");
+    assert_eq!(msg.params, ["nick", "=", "#channel"]);
+    assert_eq!(msg.trailing, Some("nick @op +voice"));
+
+    for input in ["01 nick", "0001 nick", "1a1 nick", "PRIV-MSG #channel"] {
+        assert!(matches!(
+            try_message(input),
+            Err(nom::Err::Error(MessageError {
+                error: ErrorKind::Command,
+                ..
+            }))
+        ));
+    }
+}
+
+#[test]
+fn params() {
+    let (_, msg) = try_message("MODE #channel +o nick").unwrap();
+    assert_eq!(msg.params, ["#channel", "+o", "nick"]);
+    assert_eq!(msg.trailing, None);
+
+    let (_, msg) = try_message("PRIVMSG #channel :").unwrap();
+    assert_eq!(msg.trailing, Some(""));
+
+    let (_, msg) = try_message("PRIVMSG #channel ::-) : hi ").unwrap();
+    assert_eq!(msg.trailing, Some(":-) : hi "));
+
+    let (_, msg) = try_message("USER  guest   0 * :Real Name").unwrap();
+    assert_eq!(msg.params, ["guest", "0", "*"]);
+    assert_eq!(msg.trailing, Some("Real Name"));
+
+    let input = "CMD 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16";
+    let (_, msg) = try_message(input).unwrap();
+    assert_eq!(msg.params.len(), 14);
+    assert_eq!(msg.trailing, Some("15 16"));
+}
+
+#[test]
+fn line_endings() {
+    let (remain, msg) = try_message("PING :token\r\n").unwrap();
+    assert_eq!(remain, "");
+    assert_eq!(msg.trailing, Some("token"));
+
+    let (remain, msg) = try_message("PING token\nPING next\r\n").unwrap();
+    assert_eq!(remain, "PING next\r\n");
+    assert_eq!(msg.params, ["token"]);
+
+    let (remain, msg) = try_message("QUIT\r\n").unwrap();
+    assert_eq!(remain, "");
+    assert_eq!(msg.command, "QUIT");
+}
+
+#[test]
+fn errors() {
+    assert!(matches!(
+        try_message(""),
+        Err(nom::Err::Error(MessageError {
+            error: ErrorKind::Empty,
+            ..
+        }))
+    ));
+    assert!(matches!(
+        try_message(": PRIVMSG #channel"),
+        Err(nom::Err::Error(MessageError {
+            error: ErrorKind::Empty,
+            ..
+        }))
+    ));
+    assert!(matches!(
+        try_message(":nick!user@host"),
+        Err(nom::Err::Error(MessageError {
+            error: ErrorKind::Command,
+            ..
+        }))
+    ));
+    assert!(matches!(
+        try_message("@=x PRIVMSG #channel"),
+        Err(nom::Err::Error(MessageError {
+            error: ErrorKind::EmptyKey,
+            ..
+        }))
+    ));
+    assert!(matches!(
+        try_message(":nick!user@bad_host PRIVMSG #channel"),
+        Err(nom::Err::Error(MessageError {
+            error: ErrorKind::Host,
+            ..
+        }))
+    ));
+    assert!(message("@ PRIVMSG").is_err());
+}
+
+#[test]
+fn custom_tags_parser() {
+    let input = "@user_id=1 :nick!user@host PRIVMSG #channel :Hi";
+    assert!(try_message(input).is_err());
+
+    let parser = MessageParser::new(IRCv3TagsParser::new(
+        ircv3_tags::custom_parser().allow_chars(&['_']),
+    ));
+    let (_, msg) = parser.try_message(input).unwrap();
+    assert_eq!(msg.tags.get("user_id"), Some("1"));
+    assert_eq!(msg.command, "PRIVMSG");
+}
+
+#[test]
+fn custom_host_validators() {
+    use ircv3_tags::{
+        host::{IdnHostValidator, IrcHostParser, RFC1123HostValidator, RFC952HostParser},
+        source::SourceParser,
+    };
+
+    let input = "@1password.com/id=1 :nick!user@bücher.example PRIVMSG #channel :Hi";
+    assert!(try_message(input).is_err());
+
+    let parser =
+        MessageParser::new(IRCv3TagsParser::default().host_validator(RFC1123HostValidator));
+    assert!(parser.try_message(input).is_err());
+
+    let parser = parser.source_parser(SourceParser::new(IrcHostParser::new(
+        RFC952HostParser::new(IdnHostValidator),
+    )));
+    let (remain, msg) = parser.try_message(input).unwrap();
+    assert_eq!(remain, "");
+    assert_eq!(msg.tags.get("1password.com/id"), Some("1"));
+    assert_eq!(msg.source.and_then(|s| s.host()), Some("bücher.example"));
+    assert_eq!(msg.trailing, Some("Hi"));
+}
+
+#[test]
+fn reverse_dns_source_host() {
+    let input = ":n!u@12-34-56-78.dyn.example.net PRIVMSG #c :hi";
+    let (remain, msg) = try_message(input).unwrap();
+    assert_eq!(remain, "");
+    assert_eq!(
+        msg.source.and_then(|s| s.host()),
+        Some("12-34-56-78.dyn.example.net")
+    );
+    assert_eq!(msg.command, "PRIVMSG");
+}
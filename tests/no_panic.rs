@@ -0,0 +1,101 @@
+#![allow(deprecated)]
+
+use ircv3_tags::{
+    host::{try_irc_host, IdnHostValidator, RFC1123HostValidator},
+    message::try_message,
+    source::try_source,
+    tags::{DuplicateKeyPolicy, IRCv3TagsParser, TagsSeparator},
+    IRCv3TagsError,
+};
+use proptest::prelude::*;
+
+/// Lines built from the characters that matter to the parsers.
+fn irc_line() -> impl Strategy<Value = String> {
+    "[@+=;:/ .!a-z0-9\\-_\\\\\r\n\0éü]{0,64}"
+}
+
+#[test]
+fn parse_without_tags() {
+    for input in ["", " ", "PING :server", "=a", ";", "a=b c", "\r\n"] {
+        let (remain, tags) = ircv3_tags::parse(input);
+        assert_eq!(remain, input);
+        assert!(tags.0.is_empty());
+    }
+}
+
+#[test]
+fn parse_malformed_tags() {
+    let parser = IRCv3TagsParser::default();
+    for input in ["@", "@ ", "@;", "@=", "@a=1", "@a=1\r\n", "@;;; x", "@\0 x"] {
+        let (remain, tags) = parser.parse(input);
+        assert!(input.ends_with(remain), "{:?}", input);
+        assert!(tags.0.is_empty(), "{:?}", input);
+    }
+
+    let parser = IRCv3TagsParser::default().total_tag_limit(4);
+    let (remain, tags) = parser.parse("@a=12345 PRIVMSG");
+    assert_eq!(remain, "@a=12345 PRIVMSG");
+    assert!(tags.0.is_empty());
+}
+
+proptest! {
+    #[test]
+    fn parse_never_panics(input in any::<String>()) {
+        let (remain, _) = ircv3_tags::parse(&input);
+        prop_assert!(input.ends_with(remain));
+    }
+
+    #[test]
+    fn parsers_never_panic(input in irc_line()) {
+        let parsers = [
+            IRCv3TagsParser::default(),
+            IRCv3TagsParser::default()
+                .with_spec_limits()
+                .max_tags(3)
+                .reject_invalid_escapes()
+                .allow_empty_tags()
+                .separator(TagsSeparator::SpacesOrLineEnd)
+                .duplicate_policy(DuplicateKeyPolicy::Reject),
+            IRCv3TagsParser::default().separator(TagsSeparator::SingleSpace),
+        ];
+
+        for parser in &parsers {
+            let (remain, tags) = parser.parse(&input);
+            prop_assert!(input.ends_with(remain));
+
+            if !input.starts_with('@') {
+                prop_assert_eq!(remain, input.as_str());
+                prop_assert!(tags.0.is_empty());
+            }
+
+            if let Ok((remain, (_, diagnostics))) = parser.recover(&input) {
+                prop_assert!(input.ends_with(remain));
+                for diagnostic in diagnostics {
+                    prop_assert!(input.get(diagnostic.span.clone()).is_some());
+                    let _ = diagnostic.render(&input);
+                }
+            }
+
+            if let Err(err) = parser.debug_parse(&input) {
+                let err = IRCv3TagsError::from(err);
+                let _ = err.to_string();
+                let _ = err.render(&input);
+                if let Some(location) = err.location(&input) {
+                    prop_assert!(input.get(location.span).is_some());
+                }
+            }
+        }
+
+        let parser = IRCv3TagsParser::default().host_validator(RFC1123HostValidator);
+        let _ = parser.parse(&input);
+        let parser = IRCv3TagsParser::default().host_validator(IdnHostValidator);
+        let _ = parser.parse(&input);
+    }
+
+    #[test]
+    fn message_parsers_never_panic(input in irc_line()) {
+        let _ = try_message(&input);
+        let _ = try_source(&input);
+        let _ = try_irc_host(&input);
+    }
+}
@@ -0,0 +1,152 @@
+#![allow(deprecated)]
+
+use ircv3_tags::{serialize, unescaped_to_escaped, ErrorKind};
+
+const INPUTS: [&str; 20] = [
+    "@id=123456789;time=2025-05-04T12:34:56Z;msgid=abc123 :nick!user@host.com PRIVMSG #channel :Hello",
+    "@badge-info=subscriber/12;badges=subscriber/12,premium/1;tmi.twitch.tv/emote-only=1;room-id=12345;user-id=67890 :nick!user@host.com PRIVMSG #channel :GlitchCat",
+    "@discord.com/server-id=12345;discord.com/channel-id=67890;discord.com/message-type=regular :nick!user@host.com PRIVMSG #general :Hello everyone!",
+    "@github.com/repo=ircv3_tags;gitlab.org/issue=123;bitbucket.org/pr=456 :nick!user@host.com PRIVMSG #dev :Fixed that bug!",
+    "@+draft/reply=123456789;+typing=active;server.com/seen=2025-05-04T12:30:00Z :nick!user@host.com PRIVMSG #channel :I'm replying to your message",
+    r"@display-name=John\sDoe;message=Hello\sWorld :nick!user@host.com PRIVMSG #channel :This has escaped spaces in tags",
+    r"@id=123456789;css-style=color\:#ff0000 :nick!user@host.com PRIVMSG #channel :This has escaped semicolons in tags",
+    r"@file-path=C\\:\\\\Users\\\\Name\\\\Documents;command=echo\s\\\\ :nick!user@host.com PRIVMSG #channel :This has escaped backslashes in tags",
+    r"@multi-line=First\sLine\nSecond\sLine;formatted=Title\r\nBody :nick!user@host.com PRIVMSG #channel :This has escaped newlines in tags",
+    r"@mixed-escapes=Value\swith\sspace\sand\:\ssemicolon\sand\\backslash\r\nand\snewlines :nick!user@host.com PRIVMSG #channel :This has mixed escaped characters",
+    "@badge-info=subscriber/8;badges=subscriber/6,premium/1;color=#0000FF;display-name=TwitchUser123;emotes=25:0-4,12-16;flags=;id=b34ccfc7-4977-403a-8a94-33c6bac34fb8;mod=0;room-id=12345678;subscriber=1;tmi-sent-ts=1612312345678;turbo=0;user-id=87654321;user-type= :twitchuser123!twitchuser123@twitchuser123.tmi.twitch.tv PRIVMSG #channelname :Kappa This is a Kappa message",
+    "@+draft/reply=123456789;msgid=matrix-$1612312345678abcdef:matrix.org;time=2023-05-10T15:23:45.678Z :matrixuser@matrix.org PRIVMSG #matrix-bridged-channel :This message was sent from Matrix and bridged to IRC",
+    r"@badge=admin/1,founder/1;color=#FF0000;display-name=GameMaster;game-rank=100;guild=Defenders\sof\sLight;id=game-123456789;server=us-west;user-id=12345 :gamemaster@gamechat.example.com PRIVMSG #global-chat :Server maintenance in 10 minutes, please finish your quests",
+    "@escaped=a\\:b\\sc\\\\d\\re\\nf;normal=value :rest",
+    "@key=value\\: :nick!user@host PRIVMSG #channel :Hello",
+    "@aaa=bbb;ccc;example.com/ddd=eee :nick!ident@host.com PRIVMSG me :Hello",
+    "@+example-client-tag=example-value PRIVMSG #channel :Message",
+    "@+example.com/foo=bar :irc.example.com NOTICE #channel :A vendor-prefixed client-only tagged message",
+    r"@+example=raw+:=,escaped\:\s\\ :irc.example.com NOTICE #channel :Message",
+    "@+tag1;+tag2;+tag5000 TAGMSG #channel",
+];
+
+#[test]
+fn to_wire_round_trip() {
+    for input in INPUTS {
+        let (remain, tags) = ircv3_tags::parse(input);
+        let wire = tags.to_wire();
+
+        assert_eq!(format!("{}{}", wire, remain), input);
+        assert_eq!(ircv3_tags::parse(&wire), ("", tags));
+    }
+}
+
+#[test]
+fn serialize_round_trip() {
+    for input in INPUTS {
+        let (_, tags) = ircv3_tags::parse(input);
+        let unescaped = tags
+            .iter()
+            .map(|(k, v)| (*k, v.map(unescaped_to_escaped)))
+            .collect::<Vec<_>>();
+
+        let wire = serialize(unescaped.iter().map(|(k, v)| (*k, v.as_deref()))).unwrap();
+        let (remain, reparsed) = ircv3_tags::parse(&wire);
+
+        assert_eq!(remain, "");
+        assert_eq!(reparsed, tags, "{}", input);
+    }
+}
+
+#[test]
+fn serialize_escapes() {
+    assert_eq!(
+        serialize([("key", Some("a;b c\\d\re\nf"))]).unwrap(),
+        "@key=a\\:b\\sc\\\\d\\re\\nf "
+    );
+    assert_eq!(serialize([("key", Some("plain"))]).unwrap(), "@key=plain ");
+    assert_eq!(serialize([("key", Some("\\s"))]).unwrap(), "@key=\\\\s ");
+}
+
+#[test]
+fn serialize_valueless() {
+    assert_eq!(
+        serialize([("a", None), ("b", Some("")), ("+c", Some("d"))]).unwrap(),
+        "@a;b=;+c=d "
+    );
+}
+
+#[test]
+fn serialize_empty() {
+    assert_eq!(serialize(Vec::<(&str, Option<&str>)>::new()).unwrap(), "");
+    assert_eq!(ircv3_tags::IRCv3Tags(Vec::new()).to_wire(), "");
+}
+
+#[test]
+fn serialize_owned_pairs() {
+    let tags = vec![
+        ("msgid".to_string(), Some("abc".to_string())),
+        ("time".to_string(), Some("2025-05-04T12:34:56Z".to_string())),
+    ];
+
+    assert_eq!(
+        serialize(tags).unwrap(),
+        "@msgid=abc;time=2025-05-04T12:34:56Z "
+    );
+}
+
+#[test]
+fn serialize_invalid() {
+    let cases = [
+        ("", Some("x"), ErrorKind::EmptyKey),
+        ("bad key", Some("x"), ErrorKind::InvalidKeyChar),
+        ("-example.com/a", None, ErrorKind::InvalidVendor),
+        // Keys only the strict or RFC 1123 profiles accept, `parse` would drop them
+        ("1abc", Some("v"), ErrorKind::InvalidKeyStart),
+        ("-x", Some("v"), ErrorKind::InvalidKeyStart),
+        ("1password.com/x", Some("v"), ErrorKind::InvalidVendor),
+        ("example.com./x", Some("v"), ErrorKind::InvalidVendor),
+        ("a", Some("a\0b"), ErrorKind::ForbiddenValueChar),
+    ];
+    for (key, value, error) in cases {
+        let err = serialize([("ok", None), (key, value)]).unwrap_err();
+        assert_eq!(err.error, error, "{:?}", key);
+    }
+}
+
+#[test]
+fn serialize_parses_back() {
+    let tags = [
+        ("a1", Some("v")),
+        ("+typing", None),
+        ("example.com/x", Some("a b")),
+        ("bücher.example/x", Some("v")),
+        ("+Bücher.example/y", Some("")),
+    ];
+    let wire = serialize(tags).unwrap();
+    assert_eq!(
+        wire,
+        "@a1=v;+typing;example.com/x=a\\sb;xn--bcher-kva.example/x=v;+xn--bcher-kva.example/y= "
+    );
+
+    let (remain, parsed) = ircv3_tags::parse(&wire);
+    assert_eq!(remain, "");
+    assert_eq!(parsed.0.len(), tags.len());
+    for (key, value) in tags {
+        assert_eq!(
+            parsed.get_escaped(key).as_deref(),
+            Some(value.unwrap_or("")),
+            "{:?}",
+            key
+        );
+    }
+
+    // Every key serialize accepts must come back from `parse`
+    for key in [
+        "1abc",
+        "-x",
+        "1password.com/x",
+        "example.com./x",
+        "a--b.com/x",
+        "bücher.example/x",
+    ] {
+        if let Ok(wire) = serialize([(key, Some("v"))]) {
+            assert_eq!(ircv3_tags::parse(&wire).1 .0.len(), 1, "{:?}", key);
+        }
+    }
+}
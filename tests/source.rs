@@ -0,0 +1,188 @@
+#![allow(deprecated)]
+
+use ircv3_tags::{
+    source::{source, try_source, Source, SourceError},
+    ErrorKind,
+};
+
+#[test]
+fn user_sources() {
+    let (remain, src) = try_source("nick!ident@host.com").unwrap();
+    assert_eq!(remain, "");
+    assert!(src.is_user());
+    assert_eq!(src.nick(), Some("nick"));
+    assert_eq!(src.user(), Some("ident"));
+    assert_eq!(src.host(), Some("host.com"));
+    assert_eq!(src.to_string(), "nick!ident@host.com");
+
+    let (_, src) = try_source("nick@host.com").unwrap();
+    assert_eq!(
+        src,
+        Source::User {
+            nick: "nick",
+            user: None,
+            host: Some("host.com")
+        }
+    );
+
+    let (_, src) = try_source("nick!~ident").unwrap();
+    assert_eq!(
+        src,
+        Source::User {
+            nick: "nick",
+            user: Some("~ident"),
+            host: None
+        }
+    );
+
+    let (_, src) = try_source("nick").unwrap();
+    assert_eq!(
+        src,
+        Source::User {
+            nick: "nick",
+            user: None,
+            host: None
+        }
+    );
+
+    let (remain, src) =
+        try_source("twitchuser123!twitchuser123@twitchuser123.tmi.twitch.tv PRIVMSG").unwrap();
+    assert_eq!(remain, " PRIVMSG");
+    assert_eq!(src.host(), Some("twitchuser123.tmi.twitch.tv"));
+}
+
+#[test]
+fn ip_and_cloak_hosts() {
+    for host in [
+        "192.0.2.1",
+        "2001:db8::1",
+        "0::1",
+        "0::ffff:192.0.2.1",
+        "user/foo/bar",
+        "gateway/web/irccloud.com/x-abc",
+    ] {
+        let input = format!("nick!user@{}", host);
+        let (_, src) = try_source(&input).unwrap();
+        assert_eq!(src.host(), Some(host));
+    }
+
+    assert!(try_source("nick!user@2001:db8:::1").is_err());
+    assert!(try_source("nick!user@user//bar").is_err());
+}
+
+#[test]
+fn reverse_dns_hosts() {
+    for host in [
+        "12-34-56-78.dyn.example.net",
+        "1.2.3.4.in-addr.example.net",
+        "3com.com",
+        "cpe-1-2-3-4.example.com.",
+    ] {
+        let input = format!("n!u@{}", host);
+        let (remain, src) = try_source(&input).unwrap();
+        assert_eq!(remain, "");
+        assert_eq!(src.host(), Some(host));
+    }
+
+    assert!(try_source("n!u@192.0.2.300").is_err());
+    assert!(try_source("n!u@1.2.3.4.5").is_err());
+    assert!(try_source("n!u@-12.example.net").is_err());
+    assert!(try_source(&format!("n!u@{}.example.net", "a".repeat(64))).is_err());
+}
+
+#[test]
+fn server_sources() {
+    let (remain, src) = try_source("irc.example.com NOTICE * :hi").unwrap();
+    assert_eq!(remain, " NOTICE * :hi");
+    assert!(src.is_server());
+    assert_eq!(src, Source::Server("irc.example.com"));
+    assert_eq!(src.nick(), None);
+    assert_eq!(src.user(), None);
+    assert_eq!(src.host(), Some("irc.example.com"));
+    assert_eq!(src.to_string(), "irc.example.com");
+
+    assert!(source("tmi.twitch.tv").is_ok());
+
+    // Server names are checked like user hosts
+    for name in [
+        "192.0.2.1",
+        "12-34-56-78.dyn.example.net",
+        "irc.example.com.",
+    ] {
+        assert_eq!(
+            try_source(name).unwrap().1,
+            Source::Server(name),
+            "{:?}",
+            name
+        );
+    }
+    for name in ["irc.1", "1.2.3.4.5", "192.0.2.300", "irc.-a.com"] {
+        assert!(try_source(name).is_err(), "{:?}", name);
+        assert!(try_source(&format!("n!u@{}", name)).is_err(), "{:?}", name);
+    }
+    assert!(try_source("irc.example/x").is_err());
+    assert_eq!(
+        try_source("irc.1").unwrap_err(),
+        try_source("n!u@irc.1").unwrap_err()
+    );
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        try_source(""),
+        Err(nom::Err::Error(SourceError::new(
+            "",
+            ErrorKind::Empty,
+            "source must not be empty",
+        )))
+    );
+    assert_eq!(
+        try_source("!user@host.com"),
+        Err(nom::Err::Error(SourceError::new(
+            "!user@host.com",
+            ErrorKind::Empty,
+            "nick must not be empty",
+        )))
+    );
+    assert_eq!(
+        try_source("nick!@host.com"),
+        Err(nom::Err::Error(SourceError::new(
+            "@host.com",
+            ErrorKind::Empty,
+            "user must not be empty",
+        )))
+    );
+    assert_eq!(
+        try_source("nick!user@"),
+        Err(nom::Err::Error(SourceError::new(
+            "",
+            ErrorKind::Empty,
+            "label must not be empty",
+        )))
+    );
+    assert_eq!(
+        try_source("nick!user@host-.com"),
+        Err(nom::Err::Error(SourceError::new(
+            "host-.com",
+            ErrorKind::Host,
+            "label contains an invalid character",
+        )))
+    );
+    assert_eq!(
+        try_source("nick!user@host.com#x"),
+        Err(nom::Err::Error(SourceError::new(
+            "#x",
+            ErrorKind::Host,
+            "host contains an invalid character",
+        )))
+    );
+    assert_eq!(
+        try_source("irc..example.com"),
+        Err(nom::Err::Error(SourceError::new(
+            ".example.com",
+            ErrorKind::Host,
+            "label must start with an allowed character",
+        )))
+    );
+}
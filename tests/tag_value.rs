@@ -0,0 +1,122 @@
+#![allow(deprecated)]
+
+use std::borrow::Cow;
+
+use ircv3_tags::{unescaped_to_escaped_strict, RawValue, TagValue, UnescapeMode, UnescapedChars};
+use proptest::prelude::*;
+
+#[test]
+fn borrowed_without_escapes() {
+    let input = "@id=123;empty=;flag;msg=a\\sb :nick!user@host PRIVMSG #channel :Hello";
+    let (_, tags) = ircv3_tags::parse(input);
+
+    assert!(matches!(
+        tags.get_value("id").unwrap().unescape(),
+        Cow::Borrowed("123")
+    ));
+    assert!(matches!(
+        tags.get_value("empty").unwrap().unescape(),
+        Cow::Borrowed("")
+    ));
+    assert!(matches!(
+        tags.get_value("flag").unwrap().unescape(),
+        Cow::Borrowed("")
+    ));
+    assert!(matches!(
+        tags.get_value("msg").unwrap().unescape(),
+        Cow::Owned(_)
+    ));
+    assert_eq!(tags.get_value("missing"), None);
+}
+
+#[test]
+fn unescape_matches_get_escaped() {
+    let input = "@escaped=a\\:b\\sc\\\\d\\re\\nf;normal=value :rest";
+    let (_, tags) = ircv3_tags::parse(input);
+
+    for key in ["escaped", "normal"] {
+        let value = tags.get_value(key).unwrap();
+        let expected = tags.get_escaped(key).unwrap();
+
+        assert_eq!(value.unescape(), expected);
+        assert_eq!(value.to_string(), expected);
+        assert_eq!(value.chars().collect::<String>(), expected);
+    }
+
+    let map = tags.to_hashmap_values();
+    assert_eq!(map.get("escaped").unwrap().raw(), r"a\:b\sc\\d\re\nf");
+    assert_eq!(map.get("normal").unwrap().unescape(), "value");
+}
+
+#[test]
+fn chars_iterator() {
+    let value = TagValue::new(r"a\sb\:c\\d\xe\");
+    assert_eq!(
+        value.chars().collect::<Vec<_>>(),
+        ['a', ' ', 'b', ';', 'c', '\\', 'd', 'x', 'e']
+    );
+    assert_eq!(
+        UnescapedChars::new(value.raw()).collect::<Vec<_>>(),
+        ['a', ' ', 'b', ';', 'c', '\\', 'd', '\\', 'x', 'e', '\\']
+    );
+    assert_eq!(value.unescape_with(UnescapeMode::Lenient), r"a b;c\d\xe\");
+    assert_eq!(TagValue::new("").chars().next(), None);
+}
+
+proptest! {
+    #[test]
+    fn chars_match_unescaped_to_escaped_strict(value in "[a-z\\\\:srn ]*") {
+        let expected = unescaped_to_escaped_strict(&value);
+
+        prop_assert_eq!(TagValue::new(&value).chars().collect::<String>(), expected.clone());
+        prop_assert_eq!(TagValue::new(&value).to_string(), expected.clone());
+        prop_assert_eq!(TagValue::new(&value).unescape(), expected);
+    }
+}
+
+#[test]
+fn raw_value_keeps_missing_and_empty_apart() {
+    let input = "@flag;empty=;id=123;msg=a\\sb :nick!user@host PRIVMSG #channel :Hello";
+    let (_, tags) = ircv3_tags::parse(input);
+
+    assert_eq!(tags.get_raw("flag"), Some(RawValue::Absent));
+    assert_eq!(tags.get_raw("empty"), Some(RawValue::Empty));
+    assert_eq!(tags.get_raw("id"), Some(RawValue::Value("123")));
+    assert_eq!(tags.get_raw("missing"), None);
+
+    // The flattened view is unchanged
+    assert_eq!(tags.get("flag"), Some(""));
+    assert_eq!(tags.get("empty"), Some(""));
+    assert_eq!(tags.to_hashmap()["flag"], "");
+
+    for key in ["flag", "empty", "id", "msg"] {
+        assert!(tags.contains_key(key));
+    }
+    assert!(!tags.contains_key("missing"));
+    assert!(!tags.has_value("flag"));
+    assert!(tags.has_value("empty"));
+    assert!(tags.has_value("id"));
+    assert!(!tags.has_value("missing"));
+
+    let msg = tags.get_raw("msg").unwrap();
+    assert_eq!(msg.raw(), Some("a\\sb"));
+    assert_eq!(msg.value().unwrap().unescape(), "a b");
+    assert_eq!(RawValue::Absent.raw(), None);
+    assert_eq!(RawValue::Absent.value(), None);
+    assert_eq!(RawValue::Empty.raw(), Some(""));
+
+    let map = tags.to_hashmap_raw();
+    assert_eq!(map["flag"], RawValue::Absent);
+    assert_eq!(map["empty"], RawValue::Empty);
+    assert_eq!(map["id"], RawValue::Value("123"));
+
+    // Round trip through the stored form is lossless
+    for (key, value) in tags.iter() {
+        assert_eq!(tags.get_raw(key).unwrap().raw(), *value);
+    }
+
+    // The last value wins, like `get`
+    let (_, tags) = ircv3_tags::parse("@a=1;a;b;b= :nick PRIVMSG #channel :Hello");
+    assert_eq!(tags.get_raw("a"), Some(RawValue::Absent));
+    assert_eq!(tags.get_raw("b"), Some(RawValue::Empty));
+}
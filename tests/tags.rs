@@ -1,471 +1,1186 @@
-// use ircv3_tags::{
-//     tags::{CustomTagNameValidator, IRCv3TagsParser},
-//     unescaped_to_escaped,
-// };
-//
-// #[test]
-// fn basic_tags() {
-//     let input = "@id=123456789;time=2025-05-04T12:34:56Z;msgid=abc123 :nick!user@host.com PRIVMSG #channel :Hello,
-//    world!";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(
-//         remain,
-//         ":nick!user@host.com PRIVMSG #channel :Hello,
-//    world!"
-//     );
-//     assert_eq!(result.get("id"), Some("123456789"));
-//     assert_eq!(result.get("time"), Some("2025-05-04T12:34:56Z"));
-//     assert_eq!(result.get("msgid"), Some("abc123"));
-// }
-//
-// #[test]
-// fn vendor_specific() {
-//     let input = "@badge-info=subscriber/12;badges=subscriber/12,premium/1;tmi.twitch.tv/emote-only=1;room-id=12345;user-id=67890 :nick!user@host.com PRIVMSG #channel :GlitchCat";
-//
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//
-//     assert_eq!(remain, ":nick!user@host.com PRIVMSG #channel :GlitchCat");
-//     assert_eq!(result.get("badge-info"), Some("subscriber/12"));
-//     assert_eq!(result.get("badges"), Some("subscriber/12,premium/1"));
-//     assert_eq!(result.get("tmi.twitch.tv/emote-only"), Some("1"));
-//     assert_eq!(result.get("room-id"), Some("12345"));
-//     assert_eq!(result.get("user-id"), Some("67890"));
-//
-//     let input =
-//         "@discord.com/server-id=12345;discord.com/channel-id=67890;discord.com/message-type=regular :nick!user@host.com PRIVMSG #general :Hello everyone!";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(
-//         remain,
-//         ":nick!user@host.com PRIVMSG #general :Hello everyone!"
-//     );
-//     assert_eq!(result.get("discord.com/server-id"), Some("12345"));
-//     assert_eq!(result.get("discord.com/channel-id"), Some("67890"));
-//     assert_eq!(result.get("discord.com/message-type"), Some("regular"));
-//
-//     let input = "@github.com/repo=ircv3_tags;gitlab.org/issue=123;bitbucket.org/pr=456 :nick!user@host.com PRIVMSG #dev :Fixed that bug!";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(remain, ":nick!user@host.com PRIVMSG #dev :Fixed that bug!");
-//     assert_eq!(result.get("github.com/repo"), Some("ircv3_tags"));
-//     assert_eq!(result.get("gitlab.org/issue"), Some("123"));
-//     assert_eq!(result.get("bitbucket.org/pr"), Some("456"));
-//
-//     let input = "@+draft/reply=123456789;+typing=active;server.com/seen=2025-05-04T12:30:00Z :nick!user@host.com PRIVMSG #channel :I'm replying to your message";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(
-//         remain,
-//         ":nick!user@host.com PRIVMSG #channel :I'm replying to your message"
-//     );
-//     assert_eq!(result.get("+draft/reply"), Some("123456789"));
-//     assert_eq!(result.get("+typing"), Some("active"));
-//     assert_eq!(result.get("server.com/seen"), Some("2025-05-04T12:30:00Z"));
-// }
-//
-// #[test]
-// fn escaped_characters() {
-//     let input = r"@display-name=John\sDoe;message=Hello\sWorld :nick!user@host.com PRIVMSG #channel :This has escaped spaces in tags";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(
-//         remain,
-//         ":nick!user@host.com PRIVMSG #channel :This has escaped spaces in tags"
-//     );
-//     assert_eq!(result.get("display-name"), Some(r"John\sDoe"));
-//     assert_eq!(result.get("message"), Some(r"Hello\sWorld"));
-//     // Using a simpler case with properly escaped semicolon
-//     let input = r"@id=123456789;css-style=color\:#ff0000 :nick!user@host.com PRIVMSG #channel :This has escaped semicolons in tags";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(
-//         remain,
-//         ":nick!user@host.com PRIVMSG #channel :This has escaped semicolons in tags"
-//     );
-//     assert_eq!(result.get("id"), Some("123456789"));
-//     assert_eq!(result.get("css-style"), Some(r"color\:#ff0000"));
-//     //
-//     let input = r"@file-path=C\\:\\\\Users\\\\Name\\\\Documents;command=echo\s\\\\ :nick!user@host.com PRIVMSG #channel :This has escaped backslashes in tags";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(
-//         remain,
-//         ":nick!user@host.com PRIVMSG #channel :This has escaped backslashes in tags"
-//     );
-//     assert_eq!(
-//         result.get("file-path"),
-//         Some(r"C\\:\\\\Users\\\\Name\\\\Documents")
-//     );
-//     assert_eq!(result.get("command"), Some(r"echo\s\\\\"));
-//
-//     let input = r"@multi-line=First\sLine\nSecond\sLine;formatted=Title\r\nBody :nick!user@host.com PRIVMSG #channel :This has escaped newlines in tags";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(
-//         remain,
-//         ":nick!user@host.com PRIVMSG #channel :This has escaped newlines in tags"
-//     );
-//     assert_eq!(result.get("multi-line"), Some(r"First\sLine\nSecond\sLine"));
-//     assert_eq!(result.get("formatted"), Some(r"Title\r\nBody"));
-//
-//     let input = r"@mixed-escapes=Value\swith\sspace\sand\:\ssemicolon\sand\\backslash\r\nand\snewlines :nick!user@host.com PRIVMSG #channel :This has mixed escaped characters";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(
-//         remain,
-//         ":nick!user@host.com PRIVMSG #channel :This has mixed escaped characters"
-//     );
-//     assert_eq!(
-//         result.get("mixed-escapes"),
-//         Some(r"Value\swith\sspace\sand\:\ssemicolon\sand\\backslash\r\nand\snewlines")
-//     );
-// }
-//
-// #[test]
-// fn diffrent_tag_formats() {
-//     let input = "@badge-info=subscriber/8;badges=subscriber/6,premium/1;color=#0000FF;display-name=TwitchUser123;emotes=25:0-4,12-16;flags=;id=b34ccfc7-4977-403a-8a94-33c6bac34fb8;mod=0;room-id=12345678;subscriber=1;tmi-sent-ts=1612312345678;turbo=0;user-id=87654321;user-type= :twitchuser123!twitchuser123@twitchuser123.tmi.twitch.tv PRIVMSG #channelname :Kappa This is a Kappa message";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(remain,":twitchuser123!twitchuser123@twitchuser123.tmi.twitch.tv PRIVMSG #channelname :Kappa This is a Kappa message");
-//     assert_eq!(result.get("badge-info"), Some("subscriber/8"));
-//     assert_eq!(result.get("badges"), Some("subscriber/6,premium/1"));
-//     assert_eq!(result.get("color"), Some("#0000FF"));
-//     assert_eq!(result.get("display-name"), Some("TwitchUser123"));
-//     assert_eq!(result.get("emotes"), Some("25:0-4,12-16"));
-//     assert_eq!(result.get("flags"), Some(""));
-//     assert_eq!(
-//         result.get("id"),
-//         Some("b34ccfc7-4977-403a-8a94-33c6bac34fb8")
-//     );
-//     assert_eq!(result.get("mod"), Some("0"));
-//     assert_eq!(result.get("room-id"), Some("12345678"));
-//     assert_eq!(result.get("subscriber"), Some("1"));
-//     assert_eq!(result.get("tmi-sent-ts"), Some("1612312345678"));
-//     assert_eq!(result.get("turbo"), Some("0"));
-//     assert_eq!(result.get("user-id"), Some("87654321"));
-//     assert_eq!(result.get("user-type"), Some(""));
-//
-//     let input = "@+draft/reply=123456789;msgid=matrix-$1612312345678abcdef:matrix.org;time=2023-05-10T15:23:45.678Z :matrixuser@matrix.org PRIVMSG #matrix-bridged-channel :This message was sent from Matrix and bridged to IRC";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(remain, ":matrixuser@matrix.org PRIVMSG #matrix-bridged-channel :This message was sent from Matrix and bridged to IRC");
-//     assert_eq!(result.get("+draft/reply"), Some("123456789"));
-//     assert_eq!(
-//         result.get("msgid"),
-//         Some("matrix-$1612312345678abcdef:matrix.org")
-//     );
-//     assert_eq!(result.get("time"), Some("2023-05-10T15:23:45.678Z"));
-//
-//     let input = r"@badge=admin/1,founder/1;color=#FF0000;display-name=GameMaster;game-rank=100;guild=Defenders\sof\sLight;id=game-123456789;server=us-west;user-id=12345 :gamemaster@gamechat.example.com PRIVMSG #global-chat :Server maintenance in 10 minutes, please finish your quests";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(remain, ":gamemaster@gamechat.example.com PRIVMSG #global-chat :Server maintenance in 10 minutes, please finish your quests");
-//     assert_eq!(result.get("badge"), Some("admin/1,founder/1"));
-//     assert_eq!(result.get("color"), Some("#FF0000"));
-//     assert_eq!(result.get("display-name"), Some("GameMaster"));
-//     assert_eq!(result.get("game-rank"), Some("100"));
-//     assert_eq!(result.get("guild"), Some(r"Defenders\sof\sLight"));
-//     assert_eq!(result.get("id"), Some("game-123456789"));
-//     assert_eq!(result.get("server"), Some("us-west"));
-//     assert_eq!(result.get("user-id"), Some("12345"));
-//
-//     let input = "@account=contributor;time=2025-03-15T14:30:45.123Z;msgid=libera-123456789;+draft/label=commit-notification :gitbot!gitbot@services.libera.chat PRIVMSG #project-dev :New commit by developer: \"Fix memory leak in parser module - Issue #4567\" - https://github.com/project/repo/commit/a1b2c3d4";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(remain, ":gitbot!gitbot@services.libera.chat PRIVMSG #project-dev :New commit by developer: \"Fix memory leak in parser module - Issue #4567\" - https://github.com/project/repo/commit/a1b2c3d4");
-//     assert_eq!(result.get("account"), Some("contributor"));
-//     assert_eq!(result.get("time"), Some("2025-03-15T14:30:45.123Z"));
-//     assert_eq!(result.get("msgid"), Some("libera-123456789"));
-//     assert_eq!(result.get("+draft/label"), Some("commit-notification"));
-//
-//     let input = "@id=discord-123456789;discord-role=admin,moderator;avatar=https://cdn.discordapp.com/avatars/123456789/abcdef.png;time=2025-03-16T08:12:32.456Z :discorduser#1234@discord.gateway PRIVMSG #general :@everyone Important announcement about the new server rules";
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_ok());
-//     let (remain, result) = result.unwrap();
-//     assert_eq!(remain, ":discorduser#1234@discord.gateway PRIVMSG #general :@everyone Important announcement about the new server rules");
-//     assert_eq!(result.get("id"), Some("discord-123456789"));
-//     assert_eq!(result.get("discord-role"), Some("admin,moderator"));
-//     assert_eq!(
-//         result.get("avatar"),
-//         Some("https://cdn.discordapp.com/avatars/123456789/abcdef.png")
-//     );
-//     assert_eq!(result.get("time"), Some("2025-03-16T08:12:32.456Z"));
-// }
-//
-// #[test]
-// fn parse_fail_not_at_start() {
-//     let input ="badge-info=;badges=;color=#0000FF;display-name=barbar;emote-sets=0,300374282;user-id=713936733;user-type= fue";
-//
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_err());
-// }
-//
-// #[test]
-// fn parser_with_underscore() {
-//     let input = "@user_id=12345;display_name=Test_User;message=Hello_World :nick!user@host PRIVMSG #channel :Test";
-//
-//     let result = ircv3_tags::try_parse(input);
-//     assert!(result.is_err());
-//
-//     let parser = ircv3_tags::with_underscore();
-//     let result = parser.try_parse(input);
-//     assert!(result.is_ok());
-//
-//     let (remain, tags) = result.unwrap();
-//     assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Test");
-//     assert_eq!(tags.get("user_id"), Some("12345"));
-//     assert_eq!(tags.get("display_name"), Some("Test_User"));
-//     assert_eq!(tags.get("message"), Some("Hello_World"));
-// }
-//
-// #[test]
-// fn custom_parser_with_multiple_chars() {
-//     let input = "@user.id=12345;display$name=Test$User;section@param=value :nick!user@host PRIVMSG #channel :Test";
-//
-//     let parser = IRCv3TagsParser::new(CustomTagNameValidator::new().allow_chars(&['.', '$', '@']));
-//
-//     let result = parser.try_parse(input);
-//     assert!(result.is_ok());
-//
-//     let (remain, tags) = result.unwrap();
-//     assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Test");
-//     assert_eq!(tags.get("user.id"), Some("12345"));
-//     assert_eq!(tags.get("display$name"), Some("Test$User"));
-//     assert_eq!(tags.get("section@param"), Some("value"));
-// }
-//
-// #[test]
-// fn parser_with_leading_special_chars() {
-//     let input = "@_id=12345;$name=Test;@tag=value :nick!user@host PRIVMSG #channel :Test";
-//
-//     let parser = IRCv3TagsParser::new(
-//         CustomTagNameValidator::new()
-//             .allow_chars(&['_', '$', '@'])
-//             .allow_start_chars(&['_', '$', '@']),
-//     );
-//
-//     let result = parser.try_parse(input);
-//     assert!(result.is_ok());
-//
-//     let (remain, tags) = result.unwrap();
-//     assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Test");
-//     assert_eq!(tags.get("_id"), Some("12345"));
-//     assert_eq!(tags.get("$name"), Some("Test"));
-//     assert_eq!(tags.get("@tag"), Some("value"));
-// }
-//
-// #[test]
-// fn test_consuming_methods() {
-//     let input = "@escaped=a\\:b\\sc\\\\d\\re\\nf;normal=value :rest";
-//
-//     let (_, tags) = ircv3_tags::parse(input);
-//     let map = tags.into_hashmap();
-//     assert_eq!(map.get("escaped"), Some(&r"a\:b\sc\\d\re\nf"));
-//     assert_eq!(map.get("normal"), Some(&"value"));
-//
-//     let (_, tags) = ircv3_tags::parse(input);
-//     let unescaped_map = tags.into_hashmap_escaped();
-//     assert_eq!(
-//         unescaped_map.get("escaped"),
-//         Some(&"a;b c\\d\re\nf".to_string())
-//     );
-//     assert_eq!(unescaped_map.get("normal"), Some(&"value".to_string()));
-//
-//     let (_, tags) = ircv3_tags::parse(input);
-//     let owned_map = tags.into_map();
-//     assert_eq!(
-//         owned_map.get("escaped"),
-//         Some(&r"a\:b\sc\\d\re\nf".to_string())
-//     );
-//     assert_eq!(owned_map.get("normal"), Some(&"value".to_string()));
-//
-//     let (_, tags) = ircv3_tags::parse(input);
-//     let owned_unescaped_map = tags.into_map_escaped();
-//     assert_eq!(
-//         owned_unescaped_map.get("escaped"),
-//         Some(&"a;b c\\d\re\nf".to_string())
-//     );
-//     assert_eq!(
-//         owned_unescaped_map.get("normal"),
-//         Some(&"value".to_string())
-//     );
-// }
-//
-// #[test]
-// fn test_escaped_semicolon() {
-//     let input = "@key=value\\: :nick!user@host PRIVMSG #channel :Hello";
-//     let (_, tags) = ircv3_tags::parse(input);
-//     assert_eq!(tags.get("key"), Some("value\\:"));
-//     assert_eq!(unescaped_to_escaped("value\\:"), "value;");
-//
-//     let input = "@key=a\\:b\\sc\\\\d\\re\\nf :rest";
-//     let (_, tags) = ircv3_tags::parse(input);
-//     assert_eq!(tags.get("key"), Some("a\\:b\\sc\\\\d\\re\\nf"));
-//     assert_eq!(
-//         unescaped_to_escaped("a\\:b\\sc\\\\d\\re\\nf"),
-//         "a;b c\\d\re\nf"
-//     );
-// }
-//
-// #[test]
-// fn test_unescape_value() {
-//     assert_eq!(unescaped_to_escaped("hello\\sworld"), "hello world");
-//     assert_eq!(unescaped_to_escaped("semi\\:colon"), "semi;colon");
-//     assert_eq!(unescaped_to_escaped("back\\\\slash"), "back\\slash");
-//     assert_eq!(unescaped_to_escaped("new\\nline"), "new\nline");
-//     assert_eq!(
-//         unescaped_to_escaped("carriage\\rreturn"),
-//         "carriage\rreturn"
-//     );
-//     assert_eq!(unescaped_to_escaped("plain text"), "plain text");
-//     assert_eq!(unescaped_to_escaped("trailing\\"), "trailing\\");
-//     assert_eq!(unescaped_to_escaped("unknown\\xescape"), "unknown\\xescape");
-//     assert_eq!(unescaped_to_escaped(""), "");
-// }
-//
-// #[test]
-// fn test_unescaped_methods() {
-//     let input = "@escaped=a\\:b\\sc\\\\d\\re\\nf;normal=value :rest";
-//     let (_, tags) = ircv3_tags::parse(input);
-//     assert_eq!(tags.get("escaped"), Some(r"a\:b\sc\\d\re\nf"));
-//     assert_eq!(
-//         tags.get_escaped("escaped"),
-//         Some("a;b c\\d\re\nf".to_string())
-//     );
-//     assert_eq!(tags.get_escaped("normal"), Some("value".to_string()));
-//     assert_eq!(tags.get_escaped("missing"), None);
-//
-//     let unescaped_map = tags.to_hashmap_escaped();
-//     assert_eq!(
-//         unescaped_map.get("escaped"),
-//         Some(&"a;b c\\d\re\nf".to_string())
-//     );
-//     assert_eq!(unescaped_map.get("normal"), Some(&"value".to_string()));
-//
-//     let owned_map = tags.to_map();
-//     assert_eq!(
-//         owned_map.get("escaped"),
-//         Some(&r"a\:b\sc\\d\re\nf".to_string())
-//     );
-//
-//     let owned_unescaped_map = tags.to_map_escaped();
-//     assert_eq!(
-//         owned_unescaped_map.get("escaped"),
-//         Some(&"a;b c\\d\re\nf".to_string())
-//     );
-//     assert_eq!(
-//         owned_unescaped_map.get("normal"),
-//         Some(&"value".to_string())
-//     );
-// }
-//
-// // #[test]
-// // fn combine_multiple() {
-// //     // Not allow key_name '\', ':'
-// //     let input = r"@id=123456789;twitch.tv/badges=subscriber/12,premium/1;display-name=John\sDoe;css-style=color\:#ff0000\;font-weight\:bold;+client/typing=active :nick!user@host.com PRIVMSG #channel :This combines multiple tag types";
-// //     let result = ircv3_tags::try_parse(input);
-// //     assert!(result.is_err());
-// //
-// //     let result = ircv3_tags::try_parse_extend(input);
-// //     assert!(result.is_ok());
-// //     let (remain, result) = result.unwrap();
-// //     assert_eq!(
-// //         remain,
-// //         ":nick!user@host.com PRIVMSG #channel :This combines multiple tag types"
-// //     );
-// //     assert_eq!(result.get("id"), Some("123456789"));
-// //     assert_eq!(
-// //         result.get("twitch.tv/badges"),
-// //         Some("subscriber/12,premium/1")
-// //     );
-// //     assert_eq!(result.get("display-name"), Some(r"John\sDoe"));
-// //     assert_eq!(result.get("css-style"), Some(r"color\:#ff0000\"));
-// //     // Not allow key_name '\:'
-// //     assert_eq!(result.get(r"font-weight\:bold"), Some(""));
-// //     assert_eq!(result.get("+client/typing"), Some("active"));
-// //
-// //     let input =
-// //         "@badges=;github.com/repo=ircv3_tags;file-path=C:\\Program\\Files;room-id=123;user-id=456 :nick!user@host.com PRIVMSG #channel :Combined tag types with some empty values";
-// //     let result = ircv3_tags::try_parse(input);
-// //     assert!(result.is_ok());
-// //     let (remain, result) = result.unwrap();
-// //     assert_eq!(
-// //         remain,
-// //         ":nick!user@host.com PRIVMSG #channel :Combined tag types with some empty values"
-// //     );
-// //     assert_eq!(result.get("badges"), Some(""));
-// //     assert_eq!(result.get("github.com/repo"), Some("ircv3_tags"));
-// //     assert_eq!(result.get("file-path"), Some("C:\\Program\\Files"));
-// //     assert_eq!(result.get("room-id"), Some("123"));
-// //     assert_eq!(result.get("user-id"), Some("456"));
-// //
-// //     let input = r"@id=abc123;twitch.tv/emote-only=1;discord.com/reply-to=789123;comment=This\sis\sa\slong\sstring\\with\:\ssemicolons\nand\rnewlines;+draft/marked=important :nick!user@host.com PRIVMSG #channel :Complex message with all tag types";
-// //
-// //     let result = ircv3_tags::try_parse(input);
-// //     assert!(result.is_ok());
-// //     let (remain, result) = result.unwrap();
-// //     assert_eq!(
-// //         remain,
-// //         ":nick!user@host.com PRIVMSG #channel :Complex message with all tag types"
-// //     );
-// //     assert_eq!(result.get("id"), Some("abc123"));
-// //     assert_eq!(result.get("twitch.tv/emote-only"), Some("1"));
-// //     assert_eq!(result.get("discord.com/reply-to"), Some("789123"));
-// //     assert_eq!(
-// //         result.get("comment"),
-// //         Some(r"This\sis\sa\slong\sstring\\with\:\ssemicolons\nand\rnewlines")
-// //     );
-// //     assert_eq!(result.get("+draft/marked"), Some("important"));
-// //
-// //     let input = r"@id=123456789;invalid-escape=This\bshould\qdrop\invalid\chars;trailing-backslash=test\ :nick!user@host.com PRIVMSG #channel :Tests edge cases in escaping";
-// //     let result = ircv3_tags::try_parse(input);
-// //     assert!(result.is_ok());
-// //     let (remain, result) = result.unwrap();
-// //     assert_eq!(
-// //         remain,
-// //         ":nick!user@host.com PRIVMSG #channel :Tests edge cases in escaping"
-// //     );
-// //     assert_eq!(result.get("id"), Some("123456789"));
-// //     assert_eq!(
-// //         result.get("invalid-escape"),
-// //         Some(r"This\bshould\qdrop\invalid\chars")
-// //     );
-// //     assert_eq!(result.get("trailing-backslash"), Some(r"test\"));
-// //
-// //     let input = r"@server.com/id=123456;+client/highlight=true;empty=;complex-value=Multi\sline\nvalue\swith\:\ssemicolon\sand\\\backslash;twitch.tv/badges=moderator/1,subscriber/24;timestamp=2025-05-04T12\:34\:56Z :nick!user@host.com PRIVMSG #channel :This message has everything";
-// //     let result = ircv3_tags::try_parse(input);
-// //     assert!(result.is_ok());
-// //     let (remain, result) = result.unwrap();
-// //     assert_eq!(
-// //         remain,
-// //         ":nick!user@host.com PRIVMSG #channel :This message has everything"
-// //     );
-// //     assert_eq!(result.get("server.com/id"), Some("123456"));
-// //     assert_eq!(result.get("+client/highlight"), Some("true"));
-// //     assert_eq!(result.get("empty"), Some(""));
-// //     assert_eq!(
-// //         result.get("complex-value"),
-// //         Some(r"Multi\sline\nvalue\swith\:\ssemicolon\sand\\\backslash")
-// //     );
-// //     assert_eq!(
-// //         result.get("twitch.tv/badges"),
-// //         Some("moderator/1,subscriber/24")
-// //     );
-// //     assert_eq!(result.get("timestamp"), Some(r"2025-05-04T12\:34\:56Z"));
-// // }
+#![allow(deprecated)]
+
+use ircv3_tags::{
+    tags::{CustomTagNameValidator, IRCv3TagsParser},
+    unescaped_to_escaped, unescaped_to_escaped_strict, IRCv3TagsError, TagKey, UnescapeMode,
+};
+
+/// Returns the error of a parse that must fail on `input`.
+fn expect_error<'a, T: std::fmt::Debug>(
+    input: &str,
+    result: Result<T, nom::Err<IRCv3TagsError<&'a str>>>,
+) -> IRCv3TagsError<&'a str> {
+    match result {
+        Err(nom::Err::Error(e)) => e,
+        other => panic!("expected an error for {:?}, got {:?}", input, other),
+    }
+}
+
+#[test]
+fn basic_tags() {
+    let input = "@id=123456789;time=2025-05-04T12:34:56Z;msgid=abc123 :nick!user@host.com PRIVMSG #channel :Hello,
+   world!";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :Hello,
+   world!"
+    );
+    assert_eq!(result.get("id"), Some("123456789"));
+    assert_eq!(result.get("time"), Some("2025-05-04T12:34:56Z"));
+    assert_eq!(result.get("msgid"), Some("abc123"));
+}
+
+#[test]
+fn vendor_specific() {
+    let input = "@badge-info=subscriber/12;badges=subscriber/12,premium/1;tmi.twitch.tv/emote-only=1;room-id=12345;user-id=67890 :nick!user@host.com PRIVMSG #channel :GlitchCat";
+
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+
+    assert_eq!(remain, ":nick!user@host.com PRIVMSG #channel :GlitchCat");
+    assert_eq!(result.get("badge-info"), Some("subscriber/12"));
+    assert_eq!(result.get("badges"), Some("subscriber/12,premium/1"));
+    assert_eq!(result.get("tmi.twitch.tv/emote-only"), Some("1"));
+    assert_eq!(result.get("room-id"), Some("12345"));
+    assert_eq!(result.get("user-id"), Some("67890"));
+
+    let input =
+        "@discord.com/server-id=12345;discord.com/channel-id=67890;discord.com/message-type=regular :nick!user@host.com PRIVMSG #general :Hello everyone!";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #general :Hello everyone!"
+    );
+    assert_eq!(result.get("discord.com/server-id"), Some("12345"));
+    assert_eq!(result.get("discord.com/channel-id"), Some("67890"));
+    assert_eq!(result.get("discord.com/message-type"), Some("regular"));
+
+    let input = "@github.com/repo=ircv3_tags;gitlab.org/issue=123;bitbucket.org/pr=456 :nick!user@host.com PRIVMSG #dev :Fixed that bug!";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(remain, ":nick!user@host.com PRIVMSG #dev :Fixed that bug!");
+    assert_eq!(result.get("github.com/repo"), Some("ircv3_tags"));
+    assert_eq!(result.get("gitlab.org/issue"), Some("123"));
+    assert_eq!(result.get("bitbucket.org/pr"), Some("456"));
+
+    let input = "@+draft/reply=123456789;+typing=active;server.com/seen=2025-05-04T12:30:00Z :nick!user@host.com PRIVMSG #channel :I'm replying to your message";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :I'm replying to your message"
+    );
+    assert_eq!(result.get("+draft/reply"), Some("123456789"));
+    assert_eq!(result.get("+typing"), Some("active"));
+    assert_eq!(result.get("server.com/seen"), Some("2025-05-04T12:30:00Z"));
+}
+
+#[test]
+fn escaped_characters() {
+    let input = r"@display-name=John\sDoe;message=Hello\sWorld :nick!user@host.com PRIVMSG #channel :This has escaped spaces in tags";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :This has escaped spaces in tags"
+    );
+    assert_eq!(result.get("display-name"), Some(r"John\sDoe"));
+    assert_eq!(result.get("message"), Some(r"Hello\sWorld"));
+    // Using a simpler case with properly escaped semicolon
+    let input = r"@id=123456789;css-style=color\:#ff0000 :nick!user@host.com PRIVMSG #channel :This has escaped semicolons in tags";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :This has escaped semicolons in tags"
+    );
+    assert_eq!(result.get("id"), Some("123456789"));
+    assert_eq!(result.get("css-style"), Some(r"color\:#ff0000"));
+    //
+    let input = r"@file-path=C\\:\\\\Users\\\\Name\\\\Documents;command=echo\s\\\\ :nick!user@host.com PRIVMSG #channel :This has escaped backslashes in tags";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :This has escaped backslashes in tags"
+    );
+    assert_eq!(
+        result.get("file-path"),
+        Some(r"C\\:\\\\Users\\\\Name\\\\Documents")
+    );
+    assert_eq!(result.get("command"), Some(r"echo\s\\\\"));
+
+    let input = r"@multi-line=First\sLine\nSecond\sLine;formatted=Title\r\nBody :nick!user@host.com PRIVMSG #channel :This has escaped newlines in tags";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :This has escaped newlines in tags"
+    );
+    assert_eq!(result.get("multi-line"), Some(r"First\sLine\nSecond\sLine"));
+    assert_eq!(result.get("formatted"), Some(r"Title\r\nBody"));
+
+    let input = r"@mixed-escapes=Value\swith\sspace\sand\:\ssemicolon\sand\\backslash\r\nand\snewlines :nick!user@host.com PRIVMSG #channel :This has mixed escaped characters";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :This has mixed escaped characters"
+    );
+    assert_eq!(
+        result.get("mixed-escapes"),
+        Some(r"Value\swith\sspace\sand\:\ssemicolon\sand\\backslash\r\nand\snewlines")
+    );
+}
+
+#[test]
+fn diffrent_tag_formats() {
+    let input = "@badge-info=subscriber/8;badges=subscriber/6,premium/1;color=#0000FF;display-name=TwitchUser123;emotes=25:0-4,12-16;flags=;id=b34ccfc7-4977-403a-8a94-33c6bac34fb8;mod=0;room-id=12345678;subscriber=1;tmi-sent-ts=1612312345678;turbo=0;user-id=87654321;user-type= :twitchuser123!twitchuser123@twitchuser123.tmi.twitch.tv PRIVMSG #channelname :Kappa This is a Kappa message";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(remain,":twitchuser123!twitchuser123@twitchuser123.tmi.twitch.tv PRIVMSG #channelname :Kappa This is a Kappa message");
+    assert_eq!(result.get("badge-info"), Some("subscriber/8"));
+    assert_eq!(result.get("badges"), Some("subscriber/6,premium/1"));
+    assert_eq!(result.get("color"), Some("#0000FF"));
+    assert_eq!(result.get("display-name"), Some("TwitchUser123"));
+    assert_eq!(result.get("emotes"), Some("25:0-4,12-16"));
+    assert_eq!(result.get("flags"), Some(""));
+    assert_eq!(
+        result.get("id"),
+        Some("b34ccfc7-4977-403a-8a94-33c6bac34fb8")
+    );
+    assert_eq!(result.get("mod"), Some("0"));
+    assert_eq!(result.get("room-id"), Some("12345678"));
+    assert_eq!(result.get("subscriber"), Some("1"));
+    assert_eq!(result.get("tmi-sent-ts"), Some("1612312345678"));
+    assert_eq!(result.get("turbo"), Some("0"));
+    assert_eq!(result.get("user-id"), Some("87654321"));
+    assert_eq!(result.get("user-type"), Some(""));
+
+    let input = "@+draft/reply=123456789;msgid=matrix-$1612312345678abcdef:matrix.org;time=2023-05-10T15:23:45.678Z :matrixuser@matrix.org PRIVMSG #matrix-bridged-channel :This message was sent from Matrix and bridged to IRC";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(remain, ":matrixuser@matrix.org PRIVMSG #matrix-bridged-channel :This message was sent from Matrix and bridged to IRC");
+    assert_eq!(result.get("+draft/reply"), Some("123456789"));
+    assert_eq!(
+        result.get("msgid"),
+        Some("matrix-$1612312345678abcdef:matrix.org")
+    );
+    assert_eq!(result.get("time"), Some("2023-05-10T15:23:45.678Z"));
+
+    let input = r"@badge=admin/1,founder/1;color=#FF0000;display-name=GameMaster;game-rank=100;guild=Defenders\sof\sLight;id=game-123456789;server=us-west;user-id=12345 :gamemaster@gamechat.example.com PRIVMSG #global-chat :Server maintenance in 10 minutes, please finish your quests";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(remain, ":gamemaster@gamechat.example.com PRIVMSG #global-chat :Server maintenance in 10 minutes, please finish your quests");
+    assert_eq!(result.get("badge"), Some("admin/1,founder/1"));
+    assert_eq!(result.get("color"), Some("#FF0000"));
+    assert_eq!(result.get("display-name"), Some("GameMaster"));
+    assert_eq!(result.get("game-rank"), Some("100"));
+    assert_eq!(result.get("guild"), Some(r"Defenders\sof\sLight"));
+    assert_eq!(result.get("id"), Some("game-123456789"));
+    assert_eq!(result.get("server"), Some("us-west"));
+    assert_eq!(result.get("user-id"), Some("12345"));
+
+    let input = "@account=contributor;time=2025-03-15T14:30:45.123Z;msgid=libera-123456789;+draft/label=commit-notification :gitbot!gitbot@services.libera.chat PRIVMSG #project-dev :New commit by developer: \"Fix memory leak in parser module - Issue #4567\" - https://github.com/project/repo/commit/a1b2c3d4";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(remain, ":gitbot!gitbot@services.libera.chat PRIVMSG #project-dev :New commit by developer: \"Fix memory leak in parser module - Issue #4567\" - https://github.com/project/repo/commit/a1b2c3d4");
+    assert_eq!(result.get("account"), Some("contributor"));
+    assert_eq!(result.get("time"), Some("2025-03-15T14:30:45.123Z"));
+    assert_eq!(result.get("msgid"), Some("libera-123456789"));
+    assert_eq!(result.get("+draft/label"), Some("commit-notification"));
+
+    let input = "@id=discord-123456789;discord-role=admin,moderator;avatar=https://cdn.discordapp.com/avatars/123456789/abcdef.png;time=2025-03-16T08:12:32.456Z :discorduser#1234@discord.gateway PRIVMSG #general :@everyone Important announcement about the new server rules";
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_ok());
+    let (remain, result) = result.unwrap();
+    assert_eq!(remain, ":discorduser#1234@discord.gateway PRIVMSG #general :@everyone Important announcement about the new server rules");
+    assert_eq!(result.get("id"), Some("discord-123456789"));
+    assert_eq!(result.get("discord-role"), Some("admin,moderator"));
+    assert_eq!(
+        result.get("avatar"),
+        Some("https://cdn.discordapp.com/avatars/123456789/abcdef.png")
+    );
+    assert_eq!(result.get("time"), Some("2025-03-16T08:12:32.456Z"));
+}
+
+#[test]
+fn parse_fail_not_at_start() {
+    let input ="badge-info=;badges=;color=#0000FF;display-name=barbar;emote-sets=0,300374282;user-id=713936733;user-type= fue";
+
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_err());
+}
+
+#[test]
+fn parser_with_underscore() {
+    let input = "@user_id=12345;display_name=Test_User;message=Hello_World :nick!user@host PRIVMSG #channel :Test";
+
+    let result = ircv3_tags::try_parse(input);
+    assert!(result.is_err());
+
+    let parser = ircv3_tags::with_underscore();
+    let result = parser.try_parse(input);
+    assert!(result.is_ok());
+
+    let (remain, tags) = result.unwrap();
+    assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Test");
+    assert_eq!(tags.get("user_id"), Some("12345"));
+    assert_eq!(tags.get("display_name"), Some("Test_User"));
+    assert_eq!(tags.get("message"), Some("Hello_World"));
+}
+
+#[test]
+fn custom_parser_with_multiple_chars() {
+    let input = "@user.id=12345;display$name=Test$User;section@param=value :nick!user@host PRIVMSG #channel :Test";
+
+    let parser = IRCv3TagsParser::new(CustomTagNameValidator::new().allow_chars(&['.', '$', '@']));
+
+    let result = parser.try_parse(input);
+    assert!(result.is_ok());
+
+    let (remain, tags) = result.unwrap();
+    assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Test");
+    assert_eq!(tags.get("user.id"), Some("12345"));
+    assert_eq!(tags.get("display$name"), Some("Test$User"));
+    assert_eq!(tags.get("section@param"), Some("value"));
+}
+
+#[test]
+fn parser_with_leading_special_chars() {
+    let input = "@_id=12345;$name=Test;@tag=value :nick!user@host PRIVMSG #channel :Test";
+
+    let parser = IRCv3TagsParser::new(
+        CustomTagNameValidator::new()
+            .allow_chars(&['_', '$', '@'])
+            .allow_start_chars(&['_', '$', '@']),
+    );
+
+    let result = parser.try_parse(input);
+    assert!(result.is_ok());
+
+    let (remain, tags) = result.unwrap();
+    assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Test");
+    assert_eq!(tags.get("_id"), Some("12345"));
+    assert_eq!(tags.get("$name"), Some("Test"));
+    assert_eq!(tags.get("@tag"), Some("value"));
+}
+
+#[test]
+fn test_consuming_methods() {
+    let input = "@escaped=a\\:b\\sc\\\\d\\re\\nf;normal=value :rest";
+
+    let (_, tags) = ircv3_tags::parse(input);
+    let map = tags.into_hashmap();
+    assert_eq!(map.get("escaped"), Some(&r"a\:b\sc\\d\re\nf"));
+    assert_eq!(map.get("normal"), Some(&"value"));
+
+    let (_, tags) = ircv3_tags::parse(input);
+    let unescaped_map = tags.into_hashmap_escaped();
+    assert_eq!(
+        unescaped_map.get("escaped"),
+        Some(&"a;b c\\d\re\nf".to_string())
+    );
+    assert_eq!(unescaped_map.get("normal"), Some(&"value".to_string()));
+
+    let (_, tags) = ircv3_tags::parse(input);
+    let owned_map = tags.into_map();
+    assert_eq!(
+        owned_map.get("escaped"),
+        Some(&r"a\:b\sc\\d\re\nf".to_string())
+    );
+    assert_eq!(owned_map.get("normal"), Some(&"value".to_string()));
+
+    let (_, tags) = ircv3_tags::parse(input);
+    let owned_unescaped_map = tags.into_map_escaped();
+    assert_eq!(
+        owned_unescaped_map.get("escaped"),
+        Some(&"a;b c\\d\re\nf".to_string())
+    );
+    assert_eq!(
+        owned_unescaped_map.get("normal"),
+        Some(&"value".to_string())
+    );
+}
+
+#[test]
+fn test_escaped_semicolon() {
+    let input = "@key=value\\: :nick!user@host PRIVMSG #channel :Hello";
+    let (_, tags) = ircv3_tags::parse(input);
+    assert_eq!(tags.get("key"), Some("value\\:"));
+    assert_eq!(unescaped_to_escaped("value\\:"), "value;");
+
+    let input = "@key=a\\:b\\sc\\\\d\\re\\nf :rest";
+    let (_, tags) = ircv3_tags::parse(input);
+    assert_eq!(tags.get("key"), Some("a\\:b\\sc\\\\d\\re\\nf"));
+    assert_eq!(
+        unescaped_to_escaped("a\\:b\\sc\\\\d\\re\\nf"),
+        "a;b c\\d\re\nf"
+    );
+}
+
+#[test]
+fn test_unescape_value() {
+    assert_eq!(unescaped_to_escaped("hello\\sworld"), "hello world");
+    assert_eq!(unescaped_to_escaped("semi\\:colon"), "semi;colon");
+    assert_eq!(unescaped_to_escaped("back\\\\slash"), "back\\slash");
+    assert_eq!(unescaped_to_escaped("new\\nline"), "new\nline");
+    assert_eq!(
+        unescaped_to_escaped("carriage\\rreturn"),
+        "carriage\rreturn"
+    );
+    assert_eq!(unescaped_to_escaped("plain text"), "plain text");
+    assert_eq!(unescaped_to_escaped("trailing\\"), "trailing\\");
+    assert_eq!(unescaped_to_escaped("unknown\\xescape"), "unknown\\xescape");
+    assert_eq!(unescaped_to_escaped(""), "");
+}
+
+#[test]
+fn test_unescape_value_strict() {
+    assert_eq!(unescaped_to_escaped_strict("hello\\sworld"), "hello world");
+    assert_eq!(unescaped_to_escaped_strict("semi\\:colon"), "semi;colon");
+    assert_eq!(unescaped_to_escaped_strict("back\\\\slash"), "back\\slash");
+    assert_eq!(unescaped_to_escaped_strict("new\\nline"), "new\nline");
+    assert_eq!(
+        unescaped_to_escaped_strict("carriage\\rreturn"),
+        "carriage\rreturn"
+    );
+    assert_eq!(unescaped_to_escaped_strict("plain text"), "plain text");
+    assert_eq!(unescaped_to_escaped_strict("trailing\\"), "trailing");
+    assert_eq!(
+        unescaped_to_escaped_strict("unknown\\xescape"),
+        "unknownxescape"
+    );
+    assert_eq!(unescaped_to_escaped_strict("\\\\\\"), "\\");
+    assert_eq!(unescaped_to_escaped_strict(""), "");
+}
+
+#[test]
+fn test_invalid_escapes() {
+    let input = r"@id=123456789;invalid-escape=This\bshould\qdrop\invalid\chars;trailing-backslash=test\ :nick!user@host.com PRIVMSG #channel :Tests edge cases in escaping";
+    let (remain, tags) = ircv3_tags::try_parse(input).unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :Tests edge cases in escaping"
+    );
+    assert_eq!(
+        tags.get("invalid-escape"),
+        Some(r"This\bshould\qdrop\invalid\chars")
+    );
+    assert_eq!(tags.get("trailing-backslash"), Some(r"test\"));
+
+    assert_eq!(
+        tags.get_escaped("invalid-escape"),
+        Some("Thisbshouldqdropinvalidchars".to_string())
+    );
+    assert_eq!(
+        tags.get_escaped("trailing-backslash"),
+        Some("test".to_string())
+    );
+    assert_eq!(
+        tags.to_map_escaped().get("trailing-backslash"),
+        Some(&"test".to_string())
+    );
+
+    assert_eq!(
+        tags.get_escaped_with("invalid-escape", UnescapeMode::Lenient),
+        Some(r"This\bshould\qdrop\invalid\chars".to_string())
+    );
+    assert_eq!(
+        tags.get_escaped_with("trailing-backslash", UnescapeMode::Lenient),
+        Some(r"test\".to_string())
+    );
+}
+
+#[test]
+fn test_unescaped_methods() {
+    let input = "@escaped=a\\:b\\sc\\\\d\\re\\nf;normal=value :rest";
+    let (_, tags) = ircv3_tags::parse(input);
+    assert_eq!(tags.get("escaped"), Some(r"a\:b\sc\\d\re\nf"));
+    assert_eq!(
+        tags.get_escaped("escaped"),
+        Some("a;b c\\d\re\nf".to_string())
+    );
+    assert_eq!(tags.get_escaped("normal"), Some("value".to_string()));
+    assert_eq!(tags.get_escaped("missing"), None);
+
+    let unescaped_map = tags.to_hashmap_escaped();
+    assert_eq!(
+        unescaped_map.get("escaped"),
+        Some(&"a;b c\\d\re\nf".to_string())
+    );
+    assert_eq!(unescaped_map.get("normal"), Some(&"value".to_string()));
+
+    let owned_map = tags.to_map();
+    assert_eq!(
+        owned_map.get("escaped"),
+        Some(&r"a\:b\sc\\d\re\nf".to_string())
+    );
+
+    let owned_unescaped_map = tags.to_map_escaped();
+    assert_eq!(
+        owned_unescaped_map.get("escaped"),
+        Some(&"a;b c\\d\re\nf".to_string())
+    );
+    assert_eq!(
+        owned_unescaped_map.get("normal"),
+        Some(&"value".to_string())
+    );
+}
+
+#[test]
+fn combine_multiple() {
+    // '\' is not allowed in key names, so the tag after the escaped ';' is invalid
+    let input = r"@id=123456789;twitch.tv/badges=subscriber/12,premium/1;display-name=John\sDoe;css-style=color\:#ff0000\;font-weight\:bold;+client/typing=active :nick!user@host.com PRIVMSG #channel :This combines multiple tag types";
+    assert!(ircv3_tags::try_parse(input).is_err());
+
+    let (remain, tags) = ircv3_tags::parse(input);
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :This combines multiple tag types"
+    );
+    assert_eq!(tags.get("id"), Some("123456789"));
+    assert_eq!(
+        tags.get("twitch.tv/badges"),
+        Some("subscriber/12,premium/1")
+    );
+    assert_eq!(
+        tags.get_escaped("display-name"),
+        Some("John Doe".to_string())
+    );
+    assert_eq!(tags.get("css-style"), Some(r"color\:#ff0000\"));
+    assert_eq!(
+        tags.get_escaped("css-style"),
+        Some("color;#ff0000".to_string())
+    );
+    assert_eq!(tags.get(r"font-weight\:bold"), None);
+    assert_eq!(tags.get("+client/typing"), Some("active"));
+
+    let input = "@badges=;github.com/repo=ircv3_tags;file-path=C:\\Program\\Files;room-id=123;user-id=456 :nick!user@host.com PRIVMSG #channel :Combined tag types with some empty values";
+    let (remain, tags) = ircv3_tags::try_parse(input).unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :Combined tag types with some empty values"
+    );
+    assert_eq!(tags.get("badges"), Some(""));
+    assert_eq!(tags.get_escaped("badges"), Some(String::new()));
+    assert_eq!(tags.get("github.com/repo"), Some("ircv3_tags"));
+    assert_eq!(tags.get("file-path"), Some("C:\\Program\\Files"));
+    assert_eq!(
+        tags.get_escaped("file-path"),
+        Some("C:ProgramFiles".to_string())
+    );
+    assert_eq!(tags.get("room-id"), Some("123"));
+    assert_eq!(tags.get("user-id"), Some("456"));
+
+    let input = r"@id=abc123;twitch.tv/emote-only=1;discord.com/reply-to=789123;comment=This\sis\sa\slong\sstring\\with\:\ssemicolons\nand\rnewlines;+draft/marked=important :nick!user@host.com PRIVMSG #channel :Complex message with all tag types";
+    let (remain, tags) = ircv3_tags::try_parse(input).unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :Complex message with all tag types"
+    );
+    assert_eq!(tags.get("id"), Some("abc123"));
+    assert_eq!(tags.get("twitch.tv/emote-only"), Some("1"));
+    assert_eq!(tags.get("discord.com/reply-to"), Some("789123"));
+    assert_eq!(
+        tags.get_escaped("comment"),
+        Some("This is a long string\\with; semicolons\nand\rnewlines".to_string())
+    );
+    assert_eq!(tags.get("+draft/marked"), Some("important"));
+
+    let input = r"@server.com/id=123456;+client/highlight=true;empty=;complex-value=Multi\sline\nvalue\swith\:\ssemicolon\sand\\\backslash;twitch.tv/badges=moderator/1,subscriber/24;timestamp=2025-05-04T12\:34\:56Z :nick!user@host.com PRIVMSG #channel :This message has everything";
+    let (remain, tags) = ircv3_tags::try_parse(input).unwrap();
+    assert_eq!(
+        remain,
+        ":nick!user@host.com PRIVMSG #channel :This message has everything"
+    );
+    assert_eq!(tags.get("server.com/id"), Some("123456"));
+    assert_eq!(tags.get("+client/highlight"), Some("true"));
+    assert_eq!(tags.get("empty"), Some(""));
+    assert_eq!(
+        tags.get_escaped("complex-value"),
+        Some("Multi line\nvalue with; semicolon and\\backslash".to_string())
+    );
+    assert_eq!(
+        tags.get("twitch.tv/badges"),
+        Some("moderator/1,subscriber/24")
+    );
+    assert_eq!(
+        tags.get_escaped("timestamp"),
+        Some("2025-05-04T12;34;56Z".to_string())
+    );
+}
+
+#[test]
+fn tag_key_structure() {
+    let parser = IRCv3TagsParser::default();
+
+    let (remain, key) = parser.try_key("+example.com/foo=bar").unwrap();
+    assert_eq!(remain, "=bar");
+    assert_eq!(key, "+example.com/foo");
+    assert!(key.is_client_only());
+    assert_eq!(key.vendor(), Some("example.com"));
+    assert_eq!(key.name(), "foo");
+
+    let (_, key) = parser.try_key("foo=bar").unwrap();
+    assert!(!key.is_client_only());
+    assert_eq!(key.vendor(), None);
+    assert_eq!(key.name(), "foo");
+
+    let (_, key) = parser.try_key("+typing=active").unwrap();
+    assert!(key.is_client_only());
+    assert_eq!(key.vendor(), None);
+    assert_eq!(key.name(), "typing");
+
+    let (_, key) = parser.try_key("tmi.twitch.tv/emote-only=1").unwrap();
+    assert!(!key.is_client_only());
+    assert_eq!(key.vendor(), Some("tmi.twitch.tv"));
+    assert_eq!(key.name(), "emote-only");
+
+    assert!(parser.try_key("=bar").is_err());
+    assert!(ircv3_tags::try_parse("@example-.com/foo=bar :rest").is_err());
+}
+
+#[test]
+fn tag_key_lookup() {
+    let input =
+        "@+example.com/foo=bar;foo=baz;draft/reply=123 :irc.example.com NOTICE #channel :Hello";
+    let (_, tags) = ircv3_tags::parse(input);
+
+    let keys = tags.keys().collect::<Vec<_>>();
+    assert_eq!(keys, ["+example.com/foo", "foo", "draft/reply"]);
+    assert_eq!(
+        keys.iter().map(|k| k.name()).collect::<Vec<_>>(),
+        ["foo", "foo", "reply"]
+    );
+    assert_eq!(
+        keys.iter().map(|k| k.vendor()).collect::<Vec<_>>(),
+        [Some("example.com"), None, Some("draft")]
+    );
+
+    assert_eq!(tags.get(keys[0]), Some("bar"));
+    assert_eq!(tags.get(keys[1]), Some("baz"));
+    assert_eq!(tags.get(TagKey::new("draft/reply")), Some("123"));
+
+    assert_eq!(tags.get_escaped(keys[0]), Some("bar".to_string()));
+    assert_eq!(
+        tags.get_escaped_with(keys[1], UnescapeMode::Lenient),
+        Some("baz".to_string())
+    );
+    assert_eq!(tags.get_value(keys[2]).map(|v| v.raw()), Some("123"));
+    assert_eq!(tags.get_all(keys[1]).collect::<Vec<_>>(), ["baz"]);
+    assert_eq!(tags.get_all(String::from("foo")).count(), 1);
+    assert!(tags.contains_key(keys[0]));
+    assert!(tags.has_value(keys[0]));
+
+    let mut buf = tags.to_buf();
+    assert_eq!(buf.get(keys[0]), Some("bar"));
+    assert_eq!(buf.get_escaped(keys[1]), Some("baz".to_string()));
+    assert_eq!(buf.get_value(keys[2]).map(|v| v.raw()), Some("123"));
+    assert!(buf.remove(keys[2]));
+}
+
+#[test]
+fn tag_size_limits() {
+    use ircv3_tags::{tags::TagLimit, ErrorKind};
+
+    // "+a=" + 7 = 10 bytes of client-only tag data, without the '@' and ' '
+    let input = "@+a=1234567;b=12 :rest";
+
+    let parser = IRCv3TagsParser::default().client_tag_limit(10);
+    assert!(parser.try_parse(input).is_ok());
+
+    let parser = IRCv3TagsParser::default().client_tag_limit(9);
+    let err = parser.debug_parse(input).unwrap_err();
+    assert!(
+        matches!(err, nom::Err::Error(ref e) if e.error == ErrorKind::TooLong(TagLimit::ClientOnly))
+    );
+
+    // "@+a=1234567;b=12 " is 17 bytes
+    let parser = IRCv3TagsParser::default().total_tag_limit(17);
+    assert!(parser.try_parse(input).is_ok());
+
+    let parser = IRCv3TagsParser::default().total_tag_limit(16);
+    let err = parser.debug_parse(input).unwrap_err();
+    assert!(
+        matches!(err, nom::Err::Error(ref e) if e.error == ErrorKind::TooLong(TagLimit::Total))
+    );
+
+    // Server tags do not count towards the client-only limit
+    let parser = IRCv3TagsParser::default().client_tag_limit(4);
+    assert!(parser.try_parse("@time=2025-05-04T12:34:56Z :rest").is_ok());
+
+    let parser = IRCv3TagsParser::default().with_spec_limits();
+    // "@time=" + value + " "
+    let input = format!("@time={} :rest", "a".repeat(8191 - 7));
+    assert!(parser.try_parse(&input).is_ok());
+    let input = format!("@time={} :rest", "a".repeat(8191 - 6));
+    assert!(parser.try_parse(&input).is_err());
+
+    // '@' <4094 bytes of client tag data> ' ' fills the client half of 4096 bytes
+    let input = format!("@+a={};+b=1 :rest", "a".repeat(4094 - 3 - 5));
+    assert!(parser.try_parse(&input).is_ok());
+    let input = format!("@+a={};+b=1 :rest", "a".repeat(4094 - 3 - 4));
+    assert!(matches!(
+        parser.debug_parse(&input),
+        Err(nom::Err::Error(e)) if e.error == ErrorKind::TooLong(TagLimit::ClientOnly)
+    ));
+
+    // Invalid segments count too, the raw section is measured before it is split
+    let input = format!("@{} :rest", vec!["-x"; 4096].join(";"));
+    assert!(matches!(
+        parser.recover(&input),
+        Err(nom::Err::Error(e)) if e.error == ErrorKind::TooLong(TagLimit::Total)
+    ));
+    // "+-x=" + value is one byte over the client limit
+    let input = format!("@a=1;+-x={} :rest", "a".repeat(4094 - 3));
+    assert!(matches!(
+        parser.recover(&input),
+        Err(nom::Err::Error(e)) if e.error == ErrorKind::TooLong(TagLimit::ClientOnly)
+    ));
+    let input = format!("@+-x={} :rest", "a".repeat(4094 - 4));
+    let (_, (tags, diagnostics)) = parser.recover(&input).unwrap();
+    assert!(tags.0.is_empty());
+    assert_eq!(diagnostics.len(), 1);
+}
+
+#[test]
+fn duplicate_key_policy() {
+    use ircv3_tags::{tags::DuplicateKeyPolicy, ErrorKind};
+
+    let input = "@a=1;b=2;a=3;c;a :nick!user@host PRIVMSG #channel :Hello";
+
+    let (remain, tags) = ircv3_tags::parse(input);
+    assert_eq!(remain, ":nick!user@host PRIVMSG #channel :Hello");
+    assert_eq!(tags.0, vec![("a", None), ("b", Some("2")), ("c", None)]);
+    assert_eq!(tags.get("a"), Some(""));
+    assert_eq!(tags.to_hashmap().get("a"), Some(&""));
+    assert_eq!(tags.to_wire(), "@a;b=2;c ");
+
+    let (_, tags) = ircv3_tags::parse("@a=1;b=2;a=3 :rest");
+    assert_eq!(tags.to_wire(), "@a=3;b=2 ");
+
+    // Linear in the number of tags, a long hostile line must not stall the parser
+    let many = (0..40_000)
+        .map(|i| format!("k{}={}", i % 20_000, i))
+        .collect::<Vec<_>>()
+        .join(";");
+    let line = format!("@{} :rest", many);
+    let (_, tags) = ircv3_tags::parse(&line);
+    assert_eq!(tags.0.len(), 20_000);
+    assert_eq!(tags.0[0], ("k0", Some("20000")));
+
+    let parser = IRCv3TagsParser::default().duplicate_policy(DuplicateKeyPolicy::FirstWins);
+    let (_, tags) = parser.parse(input);
+    assert_eq!(
+        tags.0,
+        vec![("a", Some("1")), ("b", Some("2")), ("c", None)]
+    );
+    assert_eq!(tags.get("a"), Some("1"));
+    assert_eq!(tags.to_hashmap().get("a"), Some(&"1"));
+
+    let parser = IRCv3TagsParser::default().duplicate_policy(DuplicateKeyPolicy::KeepAll);
+    let (_, tags) = parser.parse(input);
+    assert_eq!(tags.0.len(), 5);
+    assert_eq!(tags.get("a"), Some(""));
+    assert_eq!(tags.get_all("a").collect::<Vec<_>>(), ["1", "3", ""]);
+    assert_eq!(tags.get_all("b").collect::<Vec<_>>(), ["2"]);
+    assert_eq!(tags.get_all("missing").count(), 0);
+
+    let parser = IRCv3TagsParser::default().duplicate_policy(DuplicateKeyPolicy::Reject);
+    let err = parser.debug_parse(input).unwrap_err();
+    assert!(matches!(
+        err,
+        nom::Err::Error(ref e) if e.error == ErrorKind::DuplicateKey
+            && e.input == "a=3;c;a :nick!user@host PRIVMSG #channel :Hello"
+    ));
+    assert!(parser.try_parse("@a=1;b=2 :rest").is_ok());
+}
+
+#[test]
+fn error_kinds() {
+    use ircv3_tags::{host::RFC1123HostValidator, tags::TagLimit, ErrorKind};
+
+    fn kind(
+        parser: &IRCv3TagsParser<CustomTagNameValidator, impl ircv3_tags::HostValidator>,
+        input: &str,
+    ) -> (ErrorKind, String) {
+        let e = expect_error(input, parser.debug_parse(input));
+        (e.error, e.input.to_string())
+    }
+
+    let parser = IRCv3TagsParser::new(CustomTagNameValidator::new());
+    let cases = [
+        ("a=1 PRIVMSG", ErrorKind::MissingAt, "a=1 PRIVMSG"),
+        ("", ErrorKind::MissingAt, ""),
+        ("@=1 PRIVMSG", ErrorKind::EmptyKey, "=1 PRIVMSG"),
+        ("@a;;b PRIVMSG", ErrorKind::EmptyKey, ";b PRIVMSG"),
+        ("@ PRIVMSG", ErrorKind::EmptyKey, " PRIVMSG"),
+        ("@a;-b PRIVMSG", ErrorKind::InvalidKeyStart, "-b PRIVMSG"),
+        ("@a$b=1 PRIVMSG", ErrorKind::InvalidKeyChar, "$b=1 PRIVMSG"),
+        (
+            "@example-.com/a=1 PRIVMSG",
+            ErrorKind::InvalidVendor,
+            "example-.com/a=1 PRIVMSG",
+        ),
+        ("@a=1", ErrorKind::MissingSpace, ""),
+        ("@a=1;b", ErrorKind::MissingSpace, ""),
+        ("@a=1\r\n", ErrorKind::MissingSpace, "\r\n"),
+        ("@a=1;b\nc/d X", ErrorKind::MissingSpace, "\nc/d X"),
+        ("@a=1;b\0c/d X", ErrorKind::InvalidKeyChar, "\0c/d X"),
+        (
+            "@a=1\0b PRIVMSG",
+            ErrorKind::ForbiddenValueChar,
+            "\0b PRIVMSG",
+        ),
+        (
+            "@a=1\rb PRIVMSG",
+            ErrorKind::ForbiddenValueChar,
+            "\rb PRIVMSG",
+        ),
+    ];
+    for (input, error, remain) in cases {
+        assert_eq!(
+            kind(&parser, input),
+            (error, remain.to_string()),
+            "{:?}",
+            input
+        );
+    }
+
+    // The key lookahead for a vendor stops at the end of the line
+    let line = "@a=1;b\nc/d X";
+    assert_eq!(
+        expect_error(line, IRCv3TagsParser::default().recover(line)).error,
+        ErrorKind::MissingSpace
+    );
+
+    let parser = IRCv3TagsParser::new(CustomTagNameValidator::new()).max_tags(2);
+    assert!(parser.try_parse("@a;b PRIVMSG").is_ok());
+    assert_eq!(
+        kind(&parser, "@a;b;c;d PRIVMSG"),
+        (ErrorKind::TooManyTags, "c;d PRIVMSG".to_string())
+    );
+
+    let parser = IRCv3TagsParser::new(CustomTagNameValidator::new()).total_tag_limit(5);
+    assert_eq!(
+        kind(&parser, "@a=123 PRIVMSG").0,
+        ErrorKind::TooLong(TagLimit::Total)
+    );
+
+    let parser = IRCv3TagsParser::new(CustomTagNameValidator::new())
+        .duplicate_policy(ircv3_tags::tags::DuplicateKeyPolicy::Reject);
+    assert_eq!(
+        kind(&parser, "@a;b;a PRIVMSG"),
+        (ErrorKind::DuplicateKey, "a PRIVMSG".to_string())
+    );
+
+    let parser = IRCv3TagsParser::new(CustomTagNameValidator::new()).reject_invalid_escapes();
+    assert!(parser.try_parse("@a=\\:\\s\\\\\\r\\n PRIVMSG").is_ok());
+    assert_eq!(
+        kind(&parser, "@a=ok;b=x\\y PRIVMSG"),
+        (ErrorKind::InvalidEscape, "\\y PRIVMSG".to_string())
+    );
+    assert_eq!(
+        kind(&parser, "@a=x\\ PRIVMSG"),
+        (ErrorKind::InvalidEscape, "\\ PRIVMSG".to_string())
+    );
+
+    let parser =
+        IRCv3TagsParser::new(CustomTagNameValidator::new()).host_validator(RFC1123HostValidator);
+    assert_eq!(
+        kind(&parser, "@-example.com/a PRIVMSG").0,
+        ErrorKind::InvalidVendor
+    );
+}
+
+#[test]
+fn recover_skips_invalid_tags() {
+    use ircv3_tags::ErrorKind;
+
+    let parser = IRCv3TagsParser::default();
+
+    let input = "@id=1;-bad=x;example-.com/foo=2;;ok;a=b\tc;+typing=active :nick PRIVMSG #c :Hi";
+    let (remain, (tags, diagnostics)) = parser.recover(input).unwrap();
+    assert_eq!(remain, ":nick PRIVMSG #c :Hi");
+    assert_eq!(
+        tags.0,
+        vec![
+            ("id", Some("1")),
+            ("ok", None),
+            ("a", Some("b\tc")),
+            ("+typing", Some("active"))
+        ]
+    );
+
+    let skipped = diagnostics
+        .iter()
+        .map(|d| (&input[d.span.clone()], d.error.error))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        skipped,
+        [
+            ("-bad=x", ErrorKind::InvalidKeyStart),
+            ("example-.com/foo=2", ErrorKind::InvalidVendor),
+            ("", ErrorKind::EmptyKey),
+        ]
+    );
+    assert_eq!(diagnostics[2].span, 32..32);
+
+    let rendered = diagnostics[0].render(input);
+    assert!(rendered.starts_with("error: tag key must start with an allowed character\n"));
+    assert!(rendered.ends_with("  |       ^~~~~~\n"));
+
+    let (remain, (tags, diagnostics)) = parser.recover("@a=1;b=2 PRIVMSG").unwrap();
+    assert_eq!(remain, "PRIVMSG");
+    assert_eq!(tags.0, vec![("a", Some("1")), ("b", Some("2"))]);
+    assert!(diagnostics.is_empty());
+
+    let (_, (tags, diagnostics)) = parser.recover("@-a;=b PRIVMSG").unwrap();
+    assert!(tags.0.is_empty());
+    assert_eq!(diagnostics.len(), 2);
+}
+
+#[test]
+fn recover_fatal_errors() {
+    use ircv3_tags::{tags::DuplicateKeyPolicy, ErrorKind};
+
+    let parser = IRCv3TagsParser::default();
+    let error = |input| expect_error(input, parser.recover(input)).error;
+    assert_eq!(error("PRIVMSG #channel"), ErrorKind::MissingAt);
+    assert_eq!(error("@a=1;-b"), ErrorKind::MissingSpace);
+
+    // The tags section ends at a line terminator, it never runs into the next line
+    assert_eq!(error("@a=1\r\nPING :x"), ErrorKind::MissingSpace);
+    assert_eq!(error("@a=1;-b\nPING :x"), ErrorKind::MissingSpace);
+    assert_eq!(error("@a=1\0b :x"), ErrorKind::ForbiddenValueChar);
+    assert_eq!(
+        parser.parse("@a=1\r\nPING :x"),
+        ("@a=1\r\nPING :x", ircv3_tags::IRCv3Tags(Vec::new()))
+    );
+
+    let parser = IRCv3TagsParser::default()
+        .max_tags(1)
+        .duplicate_policy(DuplicateKeyPolicy::Reject);
+    assert!(parser.recover("@a;-b PRIVMSG").is_ok());
+    assert!(parser.recover("@a;b PRIVMSG").is_err());
+
+    let parser = IRCv3TagsParser::default().duplicate_policy(DuplicateKeyPolicy::Reject);
+    assert!(parser.recover("@a;-b;a PRIVMSG").is_err());
+}
+
+#[test]
+fn strict_key_validation() {
+    let parser = IRCv3TagsParser::strict();
+
+    let valid = [
+        ("@a=1 PRIVMSG", "a"),
+        ("@A-b-9=1 PRIVMSG", "A-b-9"),
+        ("@1st=1 PRIVMSG", "1st"),
+        ("@-x=1 PRIVMSG", "-x"),
+        ("@+typing=1 PRIVMSG", "+typing"),
+        ("@example.com/foo=1 PRIVMSG", "example.com/foo"),
+        ("@+example.com/1-2=1 PRIVMSG", "+example.com/1-2"),
+    ];
+    for (input, key) in valid {
+        let (remain, tags) = parser.try_parse(input).unwrap();
+        assert_eq!(remain, "PRIVMSG", "{:?}", input);
+        assert_eq!(tags.get(key), Some("1"), "{:?}", input);
+    }
+
+    let invalid = [
+        "@ключ=1 PRIVMSG",
+        "@名前=1 PRIVMSG",
+        "@ké=1 PRIVMSG",
+        "@k_ey=1 PRIVMSG",
+        "@++a=1 PRIVMSG",
+        "@a+=1 PRIVMSG",
+        "@example.com/+a=1 PRIVMSG",
+        "@bücher.example/a=1 PRIVMSG",
+        "@example.com//a=1 PRIVMSG",
+        "@example.com/=1 PRIVMSG",
+    ];
+    for input in invalid {
+        assert!(parser.try_parse(input).is_err(), "{:?}", input);
+    }
+}
+
+#[test]
+fn lenient_key_validation() {
+    let input = "@kлюч=1;k名前=2;k٣=3 PRIVMSG";
+
+    assert!(IRCv3TagsParser::default().try_parse(input).is_err());
+    assert!(IRCv3TagsParser::strict().try_parse(input).is_err());
+
+    let (remain, tags) = IRCv3TagsParser::lenient().try_parse(input).unwrap();
+    assert_eq!(remain, "PRIVMSG");
+    assert_eq!(tags.get("kлюч"), Some("1"));
+    assert_eq!(tags.get("k名前"), Some("2"));
+    assert_eq!(tags.get("k٣"), Some("3"));
+
+    for input in [
+        "@ключ=1 PRIVMSG",
+        "@k_ey=1 PRIVMSG",
+        "@bücher.example/a=1 PRIVMSG",
+    ] {
+        assert!(
+            IRCv3TagsParser::lenient().try_parse(input).is_err(),
+            "{:?}",
+            input
+        );
+    }
+
+    assert!(IRCv3TagsParser::default()
+        .try_parse("@-x=1 PRIVMSG")
+        .is_err());
+    assert!(IRCv3TagsParser::lenient()
+        .try_parse("@1st=1 PRIVMSG")
+        .is_err());
+}
+
+#[test]
+fn custom_key_validation() {
+    let parser = ircv3_tags::with_underscore();
+    let (_, tags) = parser.try_parse("@a_b=1;a-1 :x").unwrap();
+    assert_eq!(tags.get("a_b"), Some("1"));
+    assert!(tags.contains_key("a-1"));
+
+    let parser = IRCv3TagsParser::new(ircv3_tags::custom_parser().allow_chars(&['$']));
+    assert!(parser.try_parse("@a$b=1 :x").is_ok());
+
+    for input in ["@a名前=1 :x", "@kлюч=1 :x", "@k٣=1 :x", "@ké=1 :x"] {
+        assert!(
+            ircv3_tags::with_underscore().try_parse(input).is_err(),
+            "{:?}",
+            input
+        );
+        assert!(parser.try_parse(input).is_err(), "{:?}", input);
+    }
+
+    // Non-ASCII characters are accepted only when allowed explicitly
+    let parser = IRCv3TagsParser::new(
+        ircv3_tags::custom_parser()
+            .allow_chars(&['é'])
+            .allow_start_chars(&['é']),
+    );
+    assert!(parser.try_parse("@ké=1;é=2 :x").is_ok());
+    assert!(parser.try_parse("@kü=1 :x").is_err());
+}
+
+#[test]
+fn optional_tags() {
+    use ircv3_tags::ErrorKind;
+
+    let parser = IRCv3TagsParser::default();
+
+    for input in [
+        "",
+        "PING :server",
+        ":nick PRIVMSG #channel :Hello",
+        " @a=1 PRIVMSG",
+    ] {
+        let (remain, tags) = parser.debug_parse_optional(input).unwrap();
+        assert_eq!(remain, input);
+        assert!(tags.0.is_empty());
+        assert_eq!(parser.try_parse_optional(input), Ok((input, tags)));
+    }
+
+    let (remain, tags) = ircv3_tags::debug_parse_optional("@a=1;b PRIVMSG").unwrap();
+    assert_eq!(remain, "PRIVMSG");
+    assert_eq!(tags.get("a"), Some("1"));
+    assert_eq!(tags.get("b"), Some(""));
+
+    let cases = [
+        ("@;a PRIVMSG", ErrorKind::EmptyKey),
+        ("@ PRIVMSG", ErrorKind::EmptyKey),
+        ("@a=1", ErrorKind::MissingSpace),
+        ("@-a PRIVMSG", ErrorKind::InvalidKeyStart),
+        ("@a=1\0 PRIVMSG", ErrorKind::ForbiddenValueChar),
+    ];
+    for (input, error) in cases {
+        let e = expect_error(input, ircv3_tags::debug_parse_optional(input));
+        assert_eq!(e.error, error, "{:?}", input);
+        assert!(ircv3_tags::try_parse_optional(input).is_err());
+    }
+}
+
+#[test]
+fn empty_tag_segments() {
+    use ircv3_tags::{ErrorKind, Severity};
+
+    let inputs = ["@a=b; :src CMD", "@a=b;;c :src CMD", "@;a=b :src CMD"];
+    for input in inputs {
+        assert!(IRCv3TagsParser::default().try_parse(input).is_err());
+    }
+
+    let parser = IRCv3TagsParser::default().allow_empty_tags();
+
+    let (remain, (tags, warnings)) = parser.debug_parse_with_warnings(inputs[0]).unwrap();
+    assert_eq!(remain, ":src CMD");
+    assert_eq!(tags.0, vec![("a", Some("b"))]);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].severity, Severity::Warning);
+    assert_eq!(warnings[0].span, 5..5);
+    assert_eq!(warnings[0].error.error, ErrorKind::EmptyKey);
+    assert_eq!(
+        warnings[0].render(inputs[0]),
+        "warning: empty tag ignored\n \
+         --> byte 5, char 5\n  \
+         |\n  \
+         | @a=b; :src CMD\n  \
+         |      ^\n"
+    );
+
+    let (remain, (tags, warnings)) = parser.debug_parse_with_warnings(inputs[1]).unwrap();
+    assert_eq!(remain, ":src CMD");
+    assert_eq!(tags.0, vec![("a", Some("b")), ("c", None)]);
+    assert_eq!(
+        warnings.iter().map(|w| w.span.clone()).collect::<Vec<_>>(),
+        vec![5..5]
+    );
+
+    let (_, (tags, warnings)) = parser.debug_parse_with_warnings(inputs[2]).unwrap();
+    assert_eq!(tags.0, vec![("a", Some("b"))]);
+    assert_eq!(warnings[0].span, 1..1);
+
+    let (_, (tags, warnings)) = parser.debug_parse_with_warnings("@a;;;b;; CMD").unwrap();
+    assert_eq!(tags.0, vec![("a", None), ("b", None)]);
+    assert_eq!(warnings.len(), 4);
+
+    let (_, tags) = parser.try_parse("@a=b; :src CMD").unwrap();
+    assert_eq!(tags.get("a"), Some("b"));
+
+    let (_, (_, warnings)) = IRCv3TagsParser::default()
+        .debug_parse_with_warnings("@a=b :src CMD")
+        .unwrap();
+    assert!(warnings.is_empty());
+
+    // Still an error without a space after the tags or with invalid tags
+    assert!(parser.try_parse("@a=b;").is_err());
+    assert!(parser.try_parse("@a=b;\r\n").is_err());
+    assert!(parser.try_parse("@a=b;;-c :src CMD").is_err());
+
+    let input = "@a=b;;-c;d :src CMD";
+    let (remain, (tags, diagnostics)) = parser.recover(input).unwrap();
+    assert_eq!(remain, ":src CMD");
+    assert_eq!(tags.0, vec![("a", Some("b")), ("d", None)]);
+    assert_eq!(
+        diagnostics
+            .iter()
+            .map(|d| (d.severity, d.span.clone()))
+            .collect::<Vec<_>>(),
+        vec![(Severity::Warning, 5..5), (Severity::Error, 6..8)]
+    );
+
+    // A section of only empty segments has no tags
+    for input in ["@ :x", "@; :x", "@;; :x"] {
+        let err = expect_error(input, parser.debug_parse(input));
+        assert_eq!(err.error, ErrorKind::EmptyKey, "{:?}", input);
+        assert_eq!(err.reason, "tags must not be empty", "{:?}", input);
+        assert!(parser.recover(input).is_err(), "{:?}", input);
+        assert!(
+            IRCv3TagsParser::default().recover(input).is_err(),
+            "{:?}",
+            input
+        );
+    }
+
+    // Empty segments count towards the size limits
+    let parser = parser.with_spec_limits();
+    let input = format!("@a=1{} :x", ";".repeat(9000));
+    for result in [
+        parser.debug_parse(&input).map(|_| ()),
+        parser.recover(&input).map(|_| ()),
+    ] {
+        assert!(matches!(
+            result,
+            Err(nom::Err::Error(e)) if e.error == ErrorKind::TooLong(ircv3_tags::tags::TagLimit::Total)
+        ));
+    }
+    let input = format!("@a=1{} :x", ";".repeat(8191 - 6));
+    assert!(parser.try_parse(&input).is_ok());
+}
+
+#[test]
+fn tags_separator() {
+    use ircv3_tags::{tags::TagsSeparator, ErrorKind};
+
+    fn error(
+        parser: &IRCv3TagsParser<impl ircv3_tags::CharValidator, impl ircv3_tags::HostValidator>,
+        input: &str,
+    ) -> ErrorKind {
+        expect_error(input, parser.debug_parse(input)).error
+    }
+
+    // The default is a run of spaces
+    let parser = IRCv3TagsParser::default();
+    assert_eq!(
+        parser.try_parse("@a=b CMD"),
+        Ok(("CMD", parser.parse("@a=b CMD").1))
+    );
+    assert_eq!(parser.try_parse("@a=b   CMD").unwrap().0, "CMD");
+    assert_eq!(parser.try_parse("@a=b ").unwrap().0, "");
+    assert_eq!(error(&parser, "@a=b"), ErrorKind::MissingSpace);
+    assert_eq!(error(&parser, "@a=b\r\n"), ErrorKind::MissingSpace);
+    assert_eq!(error(&parser, "@a\r\n"), ErrorKind::MissingSpace);
+    assert_eq!(error(&parser, "@a=b\tCMD"), ErrorKind::MissingSpace);
+
+    let parser = IRCv3TagsParser::default().separator(TagsSeparator::Spaces);
+    assert_eq!(parser.try_parse("@a=b   CMD").unwrap().0, "CMD");
+
+    let parser = IRCv3TagsParser::default().separator(TagsSeparator::SingleSpace);
+    let (remain, tags) = parser.try_parse("@a=b CMD").unwrap();
+    assert_eq!(remain, "CMD");
+    assert_eq!(tags.get("a"), Some("b"));
+    assert_eq!(parser.try_parse("@a=b ").unwrap().0, "");
+    assert_eq!(error(&parser, "@a=b  CMD"), ErrorKind::ExtraSpace);
+    assert_eq!(error(&parser, "@a  CMD"), ErrorKind::ExtraSpace);
+    assert_eq!(error(&parser, "@a=b"), ErrorKind::MissingSpace);
+    assert_eq!(error(&parser, "@a=b\r\n"), ErrorKind::MissingSpace);
+    assert!(parser.recover("@a=b  CMD").is_err());
+
+    let parser = IRCv3TagsParser::default().separator(TagsSeparator::SpacesOrLineEnd);
+    for (input, remain) in [
+        ("@a=b", ""),
+        ("@a=b\r\n", ""),
+        ("@a=b\n", ""),
+        ("@a=b\r", ""),
+        ("@a=b;c\r\n", ""),
+        ("@a=b  CMD\r\n", "CMD\r\n"),
+        ("@a=b ", ""),
+    ] {
+        let (rest, tags) = parser.try_parse(input).unwrap();
+        assert_eq!(rest, remain, "{:?}", input);
+        assert_eq!(tags.get("a"), Some("b"), "{:?}", input);
+
+        let (rest, (tags, diagnostics)) = parser.recover(input).unwrap();
+        assert_eq!(rest, remain, "{:?}", input);
+        assert_eq!(tags.get("a"), Some("b"), "{:?}", input);
+        assert!(diagnostics.is_empty(), "{:?}", input);
+    }
+    assert_eq!(parser.try_parse("@a\r\n").unwrap().1.get("a"), Some(""));
+    assert_eq!(error(&parser, "@a=b\r\nCMD"), ErrorKind::ForbiddenValueChar);
+    assert_eq!(error(&parser, "@a=b\0"), ErrorKind::ForbiddenValueChar);
+
+    // Tabs are part of the value in every mode
+    let (remain, tags) = parser.try_parse("@a=b\tCMD").unwrap();
+    assert_eq!(remain, "");
+    assert_eq!(tags.get("a"), Some("b\tCMD"));
+}
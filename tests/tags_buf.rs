@@ -0,0 +1,178 @@
+#![allow(deprecated)]
+
+use ircv3_tags::{ErrorKind, IRCv3Tags, IRCv3TagsBuf};
+
+#[test]
+fn from_parsed() {
+    let input = r"@aaa=bbb;ccc;example.com/ddd=e\se;empty= :nick!ident@host.com PRIVMSG me :Hello";
+    let (remain, tags) = ircv3_tags::parse(input);
+    let buf = tags.to_buf();
+
+    assert_eq!(buf.len(), 4);
+    assert_eq!(buf.get("aaa"), Some("bbb"));
+    assert_eq!(buf.get("ccc"), Some(""));
+    assert_eq!(buf.get("example.com/ddd"), Some(r"e\se"));
+    assert_eq!(buf.get_escaped("example.com/ddd"), Some("e e".to_string()));
+    assert_eq!(buf.get("missing"), None);
+    assert_eq!(buf.as_tags(), tags);
+    assert_eq!(format!("{}{}", buf.to_wire(), remain), input);
+    assert_eq!(IRCv3TagsBuf::from(tags), buf);
+}
+
+#[test]
+fn insert_and_set() {
+    let mut buf = IRCv3TagsBuf::new();
+    assert!(buf.is_empty());
+    assert_eq!(buf.to_wire(), "");
+
+    assert_eq!(buf.set("msgid", "abc"), Ok(None));
+    assert_eq!(buf.insert("+typing", None), Ok(None));
+    assert_eq!(buf.set("account", "a;b c\\d"), Ok(None));
+    assert_eq!(buf.to_wire(), r"@msgid=abc;+typing;account=a\:b\sc\\d ");
+
+    assert_eq!(buf.set("msgid", "xyz"), Ok(Some(Some("abc".to_string()))));
+    assert_eq!(buf.insert("+typing", Some("active")), Ok(Some(None)));
+    assert_eq!(
+        buf.to_wire(),
+        r"@msgid=xyz;+typing=active;account=a\:b\sc\\d "
+    );
+    assert_eq!(buf.get_escaped("account"), Some("a;b c\\d".to_string()));
+}
+
+#[test]
+fn remove_and_retain() {
+    let (_, tags) = ircv3_tags::parse("@a=1;b=2;c;+d=4;b=5 :rest");
+    let mut buf = tags.to_buf();
+
+    assert!(buf.remove("b"));
+    assert!(!buf.remove("b"));
+    assert_eq!(buf.to_wire(), "@a=1;c;+d=4 ");
+
+    buf.retain(|key, _| !key.starts_with('+'));
+    assert_eq!(buf.to_wire(), "@a=1;c ");
+
+    buf.retain(|_, value| value.is_some());
+    assert_eq!(buf.iter().collect::<Vec<_>>(), vec![("a", Some("1"))]);
+
+    buf.clear();
+    assert_eq!(buf.as_tags(), IRCv3Tags(vec![]));
+}
+
+#[test]
+fn round_trip() {
+    let mut buf = IRCv3TagsBuf::new();
+    buf.set("time", "2025-05-04T12:34:56.000Z").unwrap();
+    buf.set("label", "with space;and\\more\r\n").unwrap();
+    buf.insert("flag", None).unwrap();
+
+    let wire = buf.to_wire();
+    let (remain, tags) = ircv3_tags::parse(&wire);
+    assert_eq!(remain, "");
+    assert_eq!(tags.to_buf(), buf);
+    assert_eq!(
+        tags.get_escaped("label"),
+        Some("with space;and\\more\r\n".to_string())
+    );
+}
+
+#[test]
+fn invalid_keys() {
+    let mut buf = IRCv3TagsBuf::new();
+
+    let cases = [
+        ("", ErrorKind::EmptyKey),
+        ("+", ErrorKind::EmptyKey),
+        ("bad key;x", ErrorKind::InvalidKeyChar),
+        ("a;b", ErrorKind::InvalidKeyChar),
+        ("a=b", ErrorKind::InvalidKeyChar),
+        ("a\r\n", ErrorKind::InvalidKeyChar),
+        ("a\0", ErrorKind::InvalidKeyChar),
+        ("a_b", ErrorKind::InvalidKeyChar),
+        ("ключ", ErrorKind::InvalidKeyStart),
+        ("example-.com/a", ErrorKind::InvalidVendor),
+        ("example.com/", ErrorKind::EmptyKey),
+        ("1st", ErrorKind::InvalidKeyStart),
+        ("-x", ErrorKind::InvalidKeyStart),
+        ("+3com.example/a-b", ErrorKind::InvalidVendor),
+    ];
+    for (key, error) in cases {
+        let err = buf.set(key, "v").unwrap_err();
+        assert_eq!(err.error, error, "{:?}", key);
+        assert!(buf.insert(key, None).is_err(), "{:?}", key);
+    }
+    assert!(buf.is_empty());
+    assert_eq!(buf.to_wire(), "");
+
+    for key in [
+        "a",
+        "a1-",
+        "+typing",
+        "+com3.example/a-b",
+        "example.com/foo",
+    ] {
+        assert_eq!(buf.set(key, "v"), Ok(None), "{:?}", key);
+    }
+    assert_eq!(
+        buf.to_wire(),
+        "@a=v;a1-=v;+typing=v;+com3.example/a-b=v;example.com/foo=v "
+    );
+}
+
+#[test]
+fn invalid_values() {
+    let mut buf = IRCv3TagsBuf::new();
+
+    let err = buf.set("a", "x\0y").unwrap_err();
+    assert_eq!(err.error, ErrorKind::ForbiddenValueChar);
+    assert_eq!(err.input, "\0y");
+    assert!(buf.insert("a", Some("\0")).is_err());
+    assert!(buf.is_empty());
+
+    assert_eq!(buf.set("a", "x\r\ny\t"), Ok(None));
+    let wire = buf.to_wire();
+    assert_eq!(ircv3_tags::parse(&wire), ("", buf.as_tags()));
+}
+
+#[test]
+fn idn_vendor_keys() {
+    use ircv3_tags::{host::IdnHostValidator, tags::IRCv3TagsParser};
+
+    let parser = IRCv3TagsParser::default().host_validator(IdnHostValidator);
+    let (_, tags) = parser.parse("@bücher.example/x=1 :rest");
+    let mut buf = tags.to_buf();
+
+    assert_eq!(
+        buf.set("bücher.example/x", "2"),
+        Ok(Some(Some("1".to_string())))
+    );
+    assert_eq!(buf.set("+bücher.example/y", "3"), Ok(None));
+    assert_eq!(
+        buf.set("-bücher.example/x", "v").unwrap_err().error,
+        ErrorKind::InvalidVendor
+    );
+    assert_eq!(
+        buf.to_wire(),
+        "@xn--bcher-kva.example/x=2;+xn--bcher-kva.example/y=3 "
+    );
+}
+
+#[test]
+fn relay_parses_back() {
+    let input = "@msgid=abc;+typing=active :nick!user@host PRIVMSG #channel :Hello";
+    let (remain, tags) = ircv3_tags::parse(input);
+    let mut buf = tags.to_buf();
+
+    buf.set("time", "2025-05-04T12:34:56.000Z").unwrap();
+    buf.set("+bücher.example/x", "a b").unwrap();
+    assert!(buf.set("1abc", "v").is_err());
+    assert!(buf.set("1password.com/x", "v").is_err());
+
+    let line = format!("{}{}", buf.to_wire(), remain);
+    let (rest, relayed) = ircv3_tags::parse(&line);
+    assert_eq!(rest, remain);
+    assert_eq!(relayed.0.len(), buf.len());
+    assert_eq!(
+        relayed.get_escaped("+bücher.example/x"),
+        Some("a b".to_string())
+    );
+}